@@ -0,0 +1,55 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Bakes build metadata into the binary for the `/version` endpoint, and the Redoc bundle
+// for `/docs`
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock_path = Path::new(&manifest_dir).join("Cargo.lock");
+
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+
+    // Docker builds have no .git directory, so the hash can be passed in instead
+    let git_hash = env::var("GIT_HASH")
+        .ok()
+        .filter(|hash| !hash.is_empty())
+        .or_else(git_head)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BUILD_GIT_HASH={}", git_hash);
+
+    // `/docs` serves Redoc from this origin rather than a CDN; the bundle is optional
+    // (see assets/README.md), and an empty one makes the page link to the spec instead
+    println!("cargo:rerun-if-changed=assets");
+    let bundle = fs::read(Path::new(&manifest_dir).join("assets/redoc.standalone.js")).unwrap_or_default();
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set for build scripts");
+    fs::write(Path::new(&out_dir).join("redoc.standalone.js"), bundle).expect("writable OUT_DIR");
+
+    let lock = fs::read_to_string(lock_path).unwrap_or_default();
+    for (package, var) in [("solana-sdk", "BUILD_SOLANA_SDK_VERSION"), ("spl-token", "BUILD_SPL_TOKEN_VERSION")] {
+        let version = locked_version(&lock, package).unwrap_or_else(|| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
+
+fn git_head() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?;
+    Some(hash.trim().to_string()).filter(|hash| !hash.is_empty())
+}
+
+// Finds `version = "..."` in the `[[package]]` entry named `package`
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", package);
+    let mut lines = lock.lines().skip_while(|line| line.trim() != name_line);
+    lines.next()?;
+    let version = lines.next()?.trim().strip_prefix("version = \"")?;
+    Some(version.trim_end_matches('"').to_string())
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::{rngs::OsRng, RngCore};
+
+pub const CHALLENGE_TTL_SECS: i64 = 600;
+pub const SESSION_TTL_SECS: i64 = 3600;
+pub const DEFAULT_CHAIN_ID: &str = "mainnet";
+
+// Challenges are small, but unauthenticated callers create them
+const MAX_CHALLENGES: usize = 10_000;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+// A statement starting with one of these would be read back as the field block
+const FIELD_PREFIXES: &[&str] = &["URI: ", "Version: "];
+
+/// A Sign-In With Solana message. `to_text` and `parse` round-trip the canonical layout:
+///
+/// ```text
+/// {domain} wants you to sign in with your Solana account:
+/// {address}
+///
+/// {statement}
+///
+/// URI: {uri}
+/// Version: 1
+/// Chain ID: {chain id}
+/// Nonce: {nonce}
+/// Issued At: {issued at}
+/// Expiration Time: {expiration time}
+/// ```
+///
+/// The statement and URI lines are omitted when absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiwsMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: Option<String>,
+    pub version: String,
+    pub chain_id: String,
+    pub nonce: String,
+    pub issued_at: String,
+    pub expiration_time: String,
+}
+
+impl SiwsMessage {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}{}\n{}", self.domain, HEADER_SUFFIX, self.address);
+        if let Some(statement) = &self.statement {
+            text.push_str("\n\n");
+            text.push_str(statement);
+        }
+        text.push('\n');
+        if let Some(uri) = &self.uri {
+            text.push_str(&format!("\nURI: {}", uri));
+        }
+        text.push_str(&format!("\nVersion: {}", self.version));
+        text.push_str(&format!("\nChain ID: {}", self.chain_id));
+        text.push_str(&format!("\nNonce: {}", self.nonce));
+        text.push_str(&format!("\nIssued At: {}", self.issued_at));
+        text.push_str(&format!("\nExpiration Time: {}", self.expiration_time));
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || "Invalid SIWS message".to_string();
+        let mut lines = text.split('\n');
+
+        let domain = lines
+            .next()
+            .and_then(|l| l.strip_suffix(HEADER_SUFFIX))
+            .filter(|d| !d.is_empty())
+            .ok_or_else(invalid)?;
+        let address = lines.next().filter(|a| !a.is_empty()).ok_or_else(invalid)?;
+
+        // Optional statement block, then a blank line before the fields
+        let mut statement = None;
+        let mut line = lines.next();
+        if line == Some("") {
+            line = lines.next();
+            if let Some(candidate) = line {
+                if is_statement(candidate) {
+                    statement = Some(candidate.to_string());
+                    if lines.next() != Some("") {
+                        return Err(invalid());
+                    }
+                    line = lines.next();
+                }
+            }
+        }
+
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        while let Some(l) = line {
+            let (key, value) = l.split_once(": ").ok_or_else(invalid)?;
+            if fields.insert(key, value).is_some() {
+                return Err(invalid());
+            }
+            line = lines.next();
+        }
+
+        let field = |key: &str| fields.get(key).map(|v| v.to_string()).ok_or_else(invalid);
+        let message = Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement,
+            uri: fields.get("URI").map(|v| v.to_string()),
+            version: field("Version")?,
+            chain_id: field("Chain ID")?,
+            nonce: field("Nonce")?,
+            issued_at: field("Issued At")?,
+            expiration_time: field("Expiration Time")?,
+        };
+
+        // Reject anything that would not re-serialize to the exact same text
+        if message.to_text() != text {
+            return Err(invalid());
+        }
+        Ok(message)
+    }
+}
+
+/// Whether `statement` fits on its statement line and reads back as a statement.
+pub fn is_statement(statement: &str) -> bool {
+    !statement.is_empty()
+        && !statement.contains('\n')
+        && !FIELD_PREFIXES.iter().any(|prefix| statement.starts_with(prefix))
+}
+
+struct Challenge {
+    message: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct Session {
+    pub address: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Outstanding challenges (keyed by nonce) and issued sessions (keyed by token).
+#[derive(Default)]
+pub struct SiwsStore {
+    challenges: Mutex<HashMap<String, Challenge>>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SiwsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn issue_challenge(
+        &self,
+        domain: String,
+        address: String,
+        statement: Option<String>,
+        uri: Option<String>,
+        chain_id: Option<String>,
+    ) -> SiwsMessage {
+        let now = Utc::now();
+        let expires_at = now + Duration::seconds(CHALLENGE_TTL_SECS);
+
+        let message = SiwsMessage {
+            domain,
+            address,
+            statement,
+            uri,
+            version: "1".to_string(),
+            chain_id: chain_id.unwrap_or_else(|| DEFAULT_CHAIN_ID.to_string()),
+            nonce: random_token(16),
+            issued_at: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            expiration_time: expires_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        };
+
+        let mut challenges = self.challenges.lock().unwrap_or_else(|e| e.into_inner());
+        challenges.retain(|_, c| c.expires_at > now);
+        if challenges.len() >= MAX_CHALLENGES {
+            // All of them are live; the oldest is the least likely to still be signed
+            let oldest = challenges.iter().min_by_key(|(_, c)| c.expires_at).map(|(nonce, _)| nonce.clone());
+            if let Some(nonce) = oldest {
+                challenges.remove(&nonce);
+            }
+        }
+        challenges.insert(message.nonce.clone(), Challenge {
+            message: message.to_text(),
+            expires_at,
+        });
+
+        message
+    }
+
+    /// Checks `message` against its issued challenge without using it up.
+    pub fn check_challenge(&self, message: &SiwsMessage, text: &str) -> Result<(), String> {
+        let challenges = self.challenges.lock().unwrap_or_else(|e| e.into_inner());
+        matching_challenge(&challenges, message, text)
+    }
+
+    /// Consumes the challenge for `message`, so each nonce signs in at most once. Call it
+    /// only after the signature verified, so a bad signature does not burn the nonce.
+    pub fn take_challenge(&self, message: &SiwsMessage, text: &str) -> Result<(), String> {
+        let mut challenges = self.challenges.lock().unwrap_or_else(|e| e.into_inner());
+        matching_challenge(&challenges, message, text)?;
+        challenges.remove(&message.nonce);
+        Ok(())
+    }
+
+    pub fn create_session(&self, address: String) -> (String, DateTime<Utc>) {
+        let now = Utc::now();
+        let expires_at = now + Duration::seconds(SESSION_TTL_SECS);
+        let token = random_token(32);
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, s| s.expires_at > now);
+        sessions.insert(token.clone(), Session { address, expires_at });
+
+        (token, expires_at)
+    }
+
+    /// Returns the session behind a live token.
+    pub fn session(&self, token: &str) -> Option<Session> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(token).filter(|s| s.expires_at > Utc::now()).cloned()
+    }
+}
+
+fn matching_challenge(challenges: &HashMap<String, Challenge>, message: &SiwsMessage, text: &str) -> Result<(), String> {
+    let challenge = challenges
+        .get(&message.nonce)
+        .ok_or_else(|| "Unknown or already used nonce".to_string())?;
+
+    if challenge.expires_at <= Utc::now() {
+        return Err("Challenge has expired".to_string());
+    }
+    if challenge.message != text {
+        return Err("Message does not match the issued challenge".to_string());
+    }
+    Ok(())
+}
+
+fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bs58::encode(bytes).into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(statement: Option<&str>, uri: Option<&str>) -> SiwsMessage {
+        SiwsMessage {
+            domain: "example.com".to_string(),
+            address: "2xn3G838bZVb2uJjGZY7hQTKCaWDXcx65daJkhK7YwKC".to_string(),
+            statement: statement.map(str::to_string),
+            uri: uri.map(str::to_string),
+            version: "1".to_string(),
+            chain_id: "mainnet".to_string(),
+            nonce: "abc123".to_string(),
+            issued_at: "2024-01-01T00:00:00.000Z".to_string(),
+            expiration_time: "2024-01-01T00:10:00.000Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        for (statement, uri) in [(None, None), (Some("Sign in to Example"), None), (Some("Hi"), Some("https://example.com"))] {
+            let message = message(statement, uri);
+            assert_eq!(SiwsMessage::parse(&message.to_text()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn canonical_text() {
+        assert_eq!(
+            message(Some("Sign in"), Some("https://example.com")).to_text(),
+            "example.com wants you to sign in with your Solana account:\n\
+             2xn3G838bZVb2uJjGZY7hQTKCaWDXcx65daJkhK7YwKC\n\
+             \n\
+             Sign in\n\
+             \n\
+             URI: https://example.com\n\
+             Version: 1\n\
+             Chain ID: mainnet\n\
+             Nonce: abc123\n\
+             Issued At: 2024-01-01T00:00:00.000Z\n\
+             Expiration Time: 2024-01-01T00:10:00.000Z"
+        );
+    }
+
+    #[test]
+    fn non_canonical_messages_are_rejected() {
+        let text = message(Some("Sign in"), None).to_text();
+        for bad in [
+            text.replace("Nonce: abc123\n", ""),
+            text.replace("Nonce: abc123", "Nonce: abc123\nNonce: def456"),
+            text.replace("Chain ID: mainnet", "Chain ID: mainnet\nExtra: field"),
+            text.replace("\n\nSign in\n", "\nSign in\n"),
+            text.replace('\n', "\r\n"),
+            format!("{}\n", text),
+            text.replace(HEADER_SUFFIX, " wants you to sign in:"),
+        ] {
+            assert!(SiwsMessage::parse(&bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn statements_that_read_back_as_fields_are_rejected() {
+        assert!(is_statement("Sign in to Example"));
+        for statement in ["", "URI: https://evil.example", "Version: 2", "two\nlines"] {
+            assert!(!is_statement(statement), "{:?}", statement);
+        }
+    }
+
+    #[test]
+    fn challenge_is_consumed_once_and_only_on_take() {
+        let store = SiwsStore::new();
+        let issued = store.issue_challenge("example.com".into(), message(None, None).address, None, None, None);
+        let text = issued.to_text();
+
+        // Checking (e.g. before a signature turns out wrong) leaves it usable
+        store.check_challenge(&issued, &text).unwrap();
+        store.check_challenge(&issued, &text).unwrap();
+        assert!(store.take_challenge(&issued, &text.replace("mainnet", "devnet")).is_err());
+
+        store.take_challenge(&issued, &text).unwrap();
+        assert_eq!(store.take_challenge(&issued, &text).unwrap_err(), "Unknown or already used nonce");
+    }
+
+    #[test]
+    fn challenges_are_capped() {
+        let store = SiwsStore::new();
+        let address = message(None, None).address;
+        let first = store.issue_challenge("example.com".into(), address.clone(), None, None, None);
+        for _ in 0..MAX_CHALLENGES {
+            store.issue_challenge("example.com".into(), address.clone(), None, None, None);
+        }
+
+        assert_eq!(store.challenges.lock().unwrap().len(), MAX_CHALLENGES);
+        assert!(store.check_challenge(&first, &first.to_text()).is_err());
+    }
+}
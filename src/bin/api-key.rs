@@ -0,0 +1,40 @@
+//! Creates API keys for `API_KEYS_FILE`.
+//!
+//! Usage:
+//!   api-key <name> [scopes]    generate a key; scopes default to `*`
+//!   api-key --hash <key>       print the hash of an existing key
+//!
+//! The plaintext key is printed once; only the line for the keys file should be kept.
+
+use std::env;
+use rand::{rngs::OsRng, RngCore};
+use solana_http_server::middleware::api_key::{hash_key, ApiKeys};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.as_slice() {
+        [flag, key] if flag == "--hash" => println!("{}", hash_key(key)),
+        [name] | [name, _] if !name.starts_with('-') => {
+            let scopes = args.get(1).map(String::as_str).unwrap_or("*");
+
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            let key = bs58::encode(bytes).into_string();
+            let line = format!("{} {} {}", name, hash_key(&key), scopes);
+
+            // Reject bad scopes here rather than when the server loads the file
+            if let Err(e) = ApiKeys::parse(&line) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+
+            println!("key:  {}", key);
+            println!("line: {}", line);
+        }
+        _ => {
+            eprintln!("usage: api-key <name> [scopes] | api-key --hash <key>");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Redis stand-in for exercising `RedisRateLimitStore` without a Redis server.
+//!
+//! Usage:
+//!   stand-in-redis [--listen 127.0.0.1:6379] [--password secret]
+//!
+//! Speaks RESP2 and understands PING, AUTH, QUIT and `EVAL` of the rate limiter's token
+//! bucket script, which it runs natively on an in-memory store. Any other script or
+//! command is answered with an error, so only use it for the rate limiter.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use solana_http_server::ratelimit::redis::TOKEN_BUCKET_SCRIPT;
+use solana_http_server::ratelimit::resp::{self, Value};
+use solana_http_server::ratelimit::{Limit, MemoryRateLimitStore};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let address = flag("--listen").unwrap_or_else(|| "127.0.0.1:6379".to_string());
+    let password: Arc<Option<String>> = Arc::new(flag("--password"));
+    let store = Arc::new(MemoryRateLimitStore::new());
+
+    let listener = TcpListener::bind(&address).await.expect("Failed to bind address");
+    println!("stand-in redis listening on {}", address);
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(_) => continue,
+        };
+        tokio::spawn(serve(stream, store.clone(), password.clone()));
+    }
+}
+
+async fn serve(stream: TcpStream, store: Arc<MemoryRateLimitStore>, password: Arc<Option<String>>) {
+    let mut connection = BufReader::new(stream);
+    let mut authenticated = password.is_none();
+
+    loop {
+        let command = match resp::read_value(&mut connection).await {
+            Ok(Value::Array(Some(items))) => items,
+            _ => return,
+        };
+        let args: Vec<Vec<u8>> = command
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Bulk(Some(bytes)) => Some(bytes),
+                _ => None,
+            })
+            .collect();
+        let name = args.first().map(|a| String::from_utf8_lossy(a).to_ascii_uppercase()).unwrap_or_default();
+
+        let reply = match name.as_str() {
+            "PING" => Value::Simple("PONG".to_string()),
+            "QUIT" => {
+                let _ = write(&mut connection, &Value::Simple("OK".to_string())).await;
+                return;
+            }
+            "AUTH" => match (password.as_deref(), args.last()) {
+                (Some(expected), Some(given)) if args.len() >= 2 && expected.as_bytes() == given.as_slice() => {
+                    authenticated = true;
+                    Value::Simple("OK".to_string())
+                }
+                _ => Value::Error("WRONGPASS invalid password".to_string()),
+            },
+            _ if !authenticated => Value::Error("NOAUTH Authentication required.".to_string()),
+            "EVAL" => eval(&store, &args),
+            _ => Value::Error(format!("ERR unknown command '{}'", name)),
+        };
+
+        if write(&mut connection, &reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+// EVAL <script> 1 <key> <capacity> <refill per ms>
+fn eval(store: &MemoryRateLimitStore, args: &[Vec<u8>]) -> Value {
+    if args.get(1).map(Vec::as_slice) != Some(TOKEN_BUCKET_SCRIPT.as_bytes()) {
+        return Value::Error("ERR stand-in only runs the token bucket script".to_string());
+    }
+
+    let text = |i: usize| args.get(i).map(|a| String::from_utf8_lossy(a).into_owned());
+    let parsed = (|| {
+        let key = text(3)?;
+        let capacity: u32 = text(4)?.parse().ok()?;
+        let rate: f64 = text(5)?.parse().ok()?;
+        (text(2)? == "1" && capacity > 0 && rate > 0.0).then_some((key, capacity, rate))
+    })();
+    let Some((key, capacity, rate)) = parsed else {
+        return Value::Error("ERR invalid token bucket arguments".to_string());
+    };
+
+    let period = Duration::from_millis((capacity as f64 / rate).round() as u64);
+    let decision = store.acquire_now(&key, Limit { capacity, period });
+
+    Value::Array(Some(vec![
+        Value::Integer(decision.allowed as i64),
+        Value::Integer(decision.remaining as i64),
+        Value::Integer(decision.retry_after.as_millis() as i64),
+    ]))
+}
+
+async fn write(connection: &mut BufReader<TcpStream>, value: &Value) -> std::io::Result<()> {
+    let mut out = Vec::new();
+    value.encode(&mut out);
+    connection.get_mut().write_all(&out).await
+}
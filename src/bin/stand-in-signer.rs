@@ -0,0 +1,135 @@
+//! Reference external signer for exercising `RemoteSigner` without an HSM or KMS.
+//!
+//! Usage:
+//!   stand-in-signer --unix /tmp/signer.sock [--keys keys.txt]
+//!   stand-in-signer --http 127.0.0.1:7070 [--keys keys.txt]
+//!
+//! The keys file holds one base58 secret per line, optionally prefixed with `<key id>=`.
+//! Without an explicit id the key id is the public key. With no keys file a single key is
+//! generated and printed on startup.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::Arc;
+use axum::{extract::State, response::Json, routing::post, Router};
+use base64::Engine;
+use solana_http_server::signer::{RemoteSignRequest, RemoteSignResponse};
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+
+type Keys = Arc<HashMap<String, Keypair>>;
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let keys: Keys = Arc::new(match flag("--keys") {
+        Some(path) => load_keys(&path),
+        None => {
+            let keypair = Keypair::new();
+            println!("generated key {}", keypair.pubkey());
+            HashMap::from([(keypair.pubkey().to_string(), keypair)])
+        }
+    });
+
+    if let Some(path) = flag("--unix") {
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("Failed to bind unix socket");
+        println!("stand-in signer listening on unix:{}", path);
+
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(_) => continue,
+            };
+            let keys = keys.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut line = String::new();
+                if BufReader::new(read).read_line(&mut line).await.is_err() {
+                    return;
+                }
+
+                let response = match serde_json::from_str(&line) {
+                    Ok(request) => sign(&keys, request),
+                    Err(_) => rejected("Malformed request"),
+                };
+                let mut payload = serde_json::to_vec(&response).unwrap_or_default();
+                payload.push(b'\n');
+                let _ = write.write_all(&payload).await;
+            });
+        }
+    } else if let Some(addr) = flag("--http") {
+        let app = Router::new()
+            .route("/sign", post(sign_http))
+            .with_state(keys);
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .expect("Failed to bind to address");
+        println!("stand-in signer listening on http://{}", addr);
+
+        axum::serve(listener, app).await.expect("Failed to start signer");
+    } else {
+        eprintln!("usage: stand-in-signer (--unix <path> | --http <addr>) [--keys <file>]");
+        std::process::exit(2);
+    }
+}
+
+fn load_keys(path: &str) -> HashMap<String, Keypair> {
+    let contents = fs::read_to_string(path).expect("Failed to read keys file");
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (id, secret) = match line.split_once('=') {
+                Some((id, secret)) => (Some(id.trim().to_string()), secret.trim()),
+                None => (None, line),
+            };
+            let bytes = bs58::decode(secret).into_vec().expect("Invalid base58 secret in keys file");
+            let keypair = Keypair::from_bytes(&bytes).expect("Invalid secret in keys file");
+            (id.unwrap_or_else(|| keypair.pubkey().to_string()), keypair)
+        })
+        .collect()
+}
+
+async fn sign_http(
+    State(keys): State<Keys>,
+    Json(request): Json<RemoteSignRequest>,
+) -> Json<RemoteSignResponse> {
+    Json(sign(&keys, request))
+}
+
+fn sign(keys: &HashMap<String, Keypair>, request: RemoteSignRequest) -> RemoteSignResponse {
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    let keypair = match keys.get(&request.key_id) {
+        Some(kp) => kp,
+        None => return rejected("Key not found"),
+    };
+    let message = match engine.decode(&request.message) {
+        Ok(bytes) => bytes,
+        Err(_) => return rejected("Invalid message encoding"),
+    };
+
+    RemoteSignResponse {
+        pubkey: Some(keypair.pubkey().to_string()),
+        signature: Some(engine.encode(keypair.sign_message(&message))),
+        error: None,
+    }
+}
+
+fn rejected(error: &str) -> RemoteSignResponse {
+    RemoteSignResponse {
+        error: Some(error.to_string()),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,1020 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use axum::http::{header::RETRY_AFTER, HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+use crate::middleware::api_key::ApiKeys;
+use crate::middleware::limits::RequestLimits;
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+use crate::middleware::status_mode::StatusMode;
+use crate::ratelimit::{Limit, MemoryRateLimitStore, RateLimitStore, RedisRateLimitStore};
+use crate::signer::{LocalSigner, RemoteSigner, SignerBackend};
+use crate::tls::{self, ClientAuth, TlsSettings};
+use crate::upstream::RpcUpstream;
+use crate::vault::{FileKeyStore, KeyStore, MemoryKeyStore};
+
+/// Token-2022 program id; it accepts the SPL Token instruction layout for the
+/// instructions this server builds.
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Server configuration: built-in defaults, then the TOML file, then environment variables.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub cors: CorsConfig,
+    pub limits: LimitsConfig,
+    pub vault: VaultConfig,
+    pub signer: SignerConfig,
+    pub auth: AuthConfig,
+    pub tls: TlsConfig,
+    pub rate_limit: RateLimitConfig,
+    pub solana: SolanaConfig,
+    pub rent: RentConfig,
+    pub features: FeaturesConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: String,
+    /// `text` or `json`.
+    pub log_format: String,
+    /// `legacy` or `http`; see `StatusMode`.
+    pub error_status_mode: String,
+    /// gzip/brotli response compression, as negotiated through `Accept-Encoding`.
+    pub compression: bool,
+    /// How long in-flight requests may run after SIGTERM/SIGINT before they are dropped.
+    pub shutdown_timeout_secs: u64,
+    /// How long to keep accepting requests after SIGTERM/SIGINT with `/readyz` failing,
+    /// so load balancers stop routing here before the listener closes.
+    pub shutdown_delay_secs: u64,
+    /// Extra plain-HTTP listener serving only `/healthz` and `/readyz`, for probes that
+    /// cannot speak TLS or present a client certificate.
+    pub health_bind: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0:3000".to_string(),
+            log_format: "text".to_string(),
+            error_status_mode: "legacy".to_string(),
+            compression: true,
+            shutdown_timeout_secs: 30,
+            shutdown_delay_secs: 5,
+            health_bind: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Exact origins such as `https://app.example.com`, or `*` for any origin.
+    pub allowed_origins: Vec<String>,
+    /// Method names, or `*` for any method.
+    pub allowed_methods: Vec<String>,
+    /// Request header names, or `*` for any header.
+    pub allowed_headers: Vec<String>,
+    /// Lets browsers send cookies and `Authorization`; needs explicit origins, methods and headers.
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight answer.
+    pub max_age_secs: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["*".to_string()],
+            allowed_headers: vec!["*".to_string()],
+            allow_credentials: false,
+            max_age_secs: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub max_body_bytes: usize,
+    pub request_timeout_ms: u64,
+    /// Overrides of `request_timeout_ms` keyed by route, e.g. `"/keypair" = 60000`.
+    pub route_timeouts_ms: BTreeMap<String, u64>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 2 * 1024 * 1024,
+            request_timeout_ms: 30_000,
+            route_timeouts_ms: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VaultConfig {
+    /// `memory` or `file:<path>`. The file vault password only comes from `KEY_VAULT_PASSWORD`.
+    pub backend: String,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self { backend: "memory".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignerConfig {
+    /// `local`, `unix:<path>` or `http://host:port[/prefix]`.
+    pub backend: String,
+}
+
+impl Default for SignerConfig {
+    fn default() -> Self {
+        Self { backend: "local".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Keys file in the `ApiKeys::load` format.
+    pub api_keys_file: Option<String>,
+    /// Keys declared inline, in addition to the keys file.
+    pub keys: Vec<ApiKeyEntry>,
+    /// Serve every endpoint without authentication. Required to start without API keys
+    /// or client certificates, so an open server is never the result of a missing file.
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyEntry {
+    pub name: String,
+    /// Hex SHA-256 of the key, as printed by `api-key --hash`.
+    pub hash: String,
+    /// Comma-separated scopes, or `*`.
+    pub scopes: String,
+}
+
+/// TLS termination; off unless both `cert` and `key` are set.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain; reloaded when the file changes.
+    pub cert: Option<String>,
+    /// PEM private key (PKCS#8, RSA or EC).
+    pub key: Option<String>,
+    pub reload_interval_secs: u64,
+    /// PEM CA bundle that client certificates must chain to; enables mTLS.
+    pub client_ca: Option<String>,
+    /// `required` or `optional` client certificates when `client_ca` is set.
+    pub client_auth: String,
+    /// Client certificates mapped to API identities.
+    pub clients: Vec<ClientCertEntry>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            cert: None,
+            key: None,
+            reload_interval_secs: 30,
+            client_ca: None,
+            client_auth: "required".to_string(),
+            clients: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientCertEntry {
+    pub name: String,
+    /// Hex SHA-256 of the DER certificate, e.g. `openssl x509 -noout -fingerprint -sha256`.
+    pub fingerprint: String,
+    /// Comma-separated scopes, or `*`.
+    pub scopes: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Per-client limit such as `60/m`; rate limiting is off when unset.
+    pub default: Option<String>,
+    pub expensive: String,
+    /// `memory` or `redis://[:password@]host:port`.
+    pub store: String,
+    pub trust_proxy: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            expensive: "10/m".to_string(),
+            store: "memory".to_string(),
+            trust_proxy: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolanaConfig {
+    /// `mainnet`, `devnet`, `testnet` or `localnet`; the default SIWS chain id.
+    pub cluster: String,
+    /// RPC node that `/readyz` depends on.
+    pub rpc_url: Option<String>,
+    /// `spl-token`, `token-2022`, or either program id.
+    pub token_program: String,
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            cluster: "mainnet".to_string(),
+            rpc_url: None,
+            token_program: "spl-token".to_string(),
+        }
+    }
+}
+
+/// Rent parameters used to quote rent-exempt balances without asking a node.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RentConfig {
+    pub lamports_per_byte_year: u64,
+    pub exemption_threshold: f64,
+}
+
+impl Default for RentConfig {
+    fn default() -> Self {
+        let rent = Rent::default();
+        Self {
+            lamports_per_byte_year: rent.lamports_per_byte_year,
+            exemption_threshold: rent.exemption_threshold,
+        }
+    }
+}
+
+/// Endpoint groups that can be switched off; disabled groups answer like unknown paths.
+/// Health checks and `/version` are always on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesConfig {
+    pub keypair: bool,
+    pub keys: bool,
+    pub message: bool,
+    pub auth: bool,
+    pub token: bool,
+    pub precompile: bool,
+    pub transfer: bool,
+    pub rent: bool,
+    pub docs: bool,
+    pub metrics: bool,
+    pub rpc: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            keypair: true,
+            keys: true,
+            message: true,
+            auth: true,
+            token: true,
+            precompile: true,
+            transfer: true,
+            rent: true,
+            docs: true,
+            metrics: true,
+            rpc: true,
+        }
+    }
+}
+
+impl FeaturesConfig {
+    pub const GROUPS: [&'static str; 11] =
+        ["keypair", "keys", "message", "auth", "token", "precompile", "transfer", "rent", "docs", "metrics", "rpc"];
+
+    /// The endpoint groups that are switched on, as reported by `/version`.
+    pub fn enabled_groups(&self) -> Vec<&'static str> {
+        Self::GROUPS.into_iter().filter(|group| self.flag(group) == Some(&true)).collect()
+    }
+
+    pub fn enabled(&self, path: &str) -> bool {
+        let group = match path.trim_start_matches('/').split('/').next().unwrap_or("") {
+            "send" => "transfer",
+            "openapi.json" => "docs",
+            group => group,
+        };
+        self.flag(group) != Some(&false)
+    }
+
+    fn flag(&self, group: &str) -> Option<&bool> {
+        Some(match group {
+            "keypair" => &self.keypair,
+            "keys" => &self.keys,
+            "message" => &self.message,
+            "auth" => &self.auth,
+            "token" => &self.token,
+            "precompile" => &self.precompile,
+            "transfer" => &self.transfer,
+            "rent" => &self.rent,
+            "docs" => &self.docs,
+            "metrics" => &self.metrics,
+            "rpc" => &self.rpc,
+            _ => return None,
+        })
+    }
+
+    fn disable(&mut self, group: &str) -> Result<(), String> {
+        let flag = match group {
+            "keypair" => &mut self.keypair,
+            "keys" => &mut self.keys,
+            "message" => &mut self.message,
+            "auth" => &mut self.auth,
+            "token" => &mut self.token,
+            "precompile" => &mut self.precompile,
+            "transfer" => &mut self.transfer,
+            "rent" => &mut self.rent,
+            "docs" => &mut self.docs,
+            "metrics" => &mut self.metrics,
+            "rpc" => &mut self.rpc,
+            _ => return Err(format!("Unknown endpoint group: {}", group)),
+        };
+        *flag = false;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultBackend {
+    Memory,
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+impl Cluster {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "mainnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+            Cluster::Localnet => "localnet",
+        }
+    }
+}
+
+impl Config {
+    /// Loads `path` (or only the defaults when `None`) and applies environment overrides.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let mut config = match path {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+                Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            None => Config::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| format!("Invalid config: {}", e))
+    }
+
+    /// Environment variables win over the file. The names predate the config file.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        if let Some(bind) = var("BIND_ADDRESS") {
+            self.server.bind = bind;
+        }
+        if let Some(port) = var("PORT") {
+            let host = self.server.bind.rsplit_once(':').map_or("0.0.0.0", |(host, _)| host);
+            self.server.bind = format!("{}:{}", host, port);
+        }
+        if let Some(format) = var("LOG_FORMAT") {
+            self.server.log_format = format;
+        }
+        if let Some(mode) = var("ERROR_STATUS_MODE") {
+            self.server.error_status_mode = mode;
+        }
+        if let Some(timeout) = var("SHUTDOWN_TIMEOUT_SECS") {
+            self.server.shutdown_timeout_secs =
+                timeout.parse().map_err(|_| format!("Invalid SHUTDOWN_TIMEOUT_SECS: {}", timeout))?;
+        }
+        if let Some(delay) = var("SHUTDOWN_DELAY_SECS") {
+            self.server.shutdown_delay_secs =
+                delay.parse().map_err(|_| format!("Invalid SHUTDOWN_DELAY_SECS: {}", delay))?;
+        }
+        if let Some(bind) = var("HEALTH_BIND_ADDRESS") {
+            self.server.health_bind = Some(bind).filter(|bind| !bind.is_empty());
+        }
+        if let Some(compression) = var("COMPRESSION") {
+            self.server.compression = env_bool("COMPRESSION", &compression)?;
+        }
+        if let Some(origins) = var("CORS_ORIGINS") {
+            self.cors.allowed_origins = split_list(&origins);
+        }
+        if let Some(methods) = var("CORS_METHODS") {
+            self.cors.allowed_methods = split_list(&methods);
+        }
+        if let Some(headers) = var("CORS_HEADERS") {
+            self.cors.allowed_headers = split_list(&headers);
+        }
+        if let Some(credentials) = var("CORS_ALLOW_CREDENTIALS") {
+            self.cors.allow_credentials = env_bool("CORS_ALLOW_CREDENTIALS", &credentials)?;
+        }
+        if let Some(limit) = var("MAX_BODY_BYTES") {
+            self.limits.max_body_bytes = limit.parse().map_err(|_| format!("Invalid MAX_BODY_BYTES: {}", limit))?;
+        }
+        if let Some(timeout) = var("REQUEST_TIMEOUT_MS") {
+            self.limits.request_timeout_ms =
+                timeout.parse().map_err(|_| format!("Invalid REQUEST_TIMEOUT_MS: {}", timeout))?;
+        }
+        if let Some(backend) = var("KEY_VAULT") {
+            self.vault.backend = backend;
+        }
+        if let Some(backend) = var("SIGNER_BACKEND") {
+            self.signer.backend = backend;
+        }
+        if let Some(path) = var("API_KEYS_FILE") {
+            self.auth.api_keys_file = Some(path);
+        }
+        if let Some(disabled) = var("AUTH_DISABLED") {
+            self.auth.disabled = env_bool("AUTH_DISABLED", &disabled)?;
+        }
+        if let Some(cert) = var("TLS_CERT") {
+            self.tls.cert = Some(cert);
+        }
+        if let Some(key) = var("TLS_KEY") {
+            self.tls.key = Some(key);
+        }
+        if let Some(ca) = var("TLS_CLIENT_CA") {
+            self.tls.client_ca = Some(ca);
+        }
+        if let Some(limit) = var("RATE_LIMIT") {
+            self.rate_limit.default = Some(limit);
+        }
+        if let Some(limit) = var("RATE_LIMIT_EXPENSIVE") {
+            self.rate_limit.expensive = limit;
+        }
+        if let Some(store) = var("RATE_LIMIT_STORE") {
+            self.rate_limit.store = store;
+        }
+        if let Some(trust) = var("RATE_LIMIT_TRUST_PROXY") {
+            self.rate_limit.trust_proxy = env_bool("RATE_LIMIT_TRUST_PROXY", &trust)?;
+        }
+        if let Some(cluster) = var("SOLANA_CLUSTER") {
+            self.solana.cluster = cluster;
+        }
+        if let Some(url) = var("SOLANA_RPC_URL") {
+            self.solana.rpc_url = Some(url);
+        }
+        if let Some(program) = var("TOKEN_PROGRAM") {
+            self.solana.token_program = program;
+        }
+        if let Some(groups) = var("DISABLED_FEATURES") {
+            for group in split_list(&groups) {
+                self.features.disable(&group)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every setting that can be checked without opening the vault or connecting
+    /// to anything. The API keys file is read.
+    pub fn validate(&self) -> Result<(), String> {
+        self.bind_addr()?;
+        if self.health_bind_addr()? == Some(self.bind_addr()?) {
+            return Err("server.health_bind must differ from server.bind".to_string());
+        }
+        self.log_format()?;
+        self.status_mode()?;
+        if self.server.shutdown_timeout_secs == 0 {
+            return Err("server.shutdown_timeout_secs must be greater than zero".to_string());
+        }
+        let _ = self.cors_layer()?;
+        self.request_limits()?;
+        if let VaultBackend::File(_) = self.vault_backend()? {
+            if std::env::var("KEY_VAULT_PASSWORD").is_err() {
+                return Err("KEY_VAULT_PASSWORD must be set for a file vault".to_string());
+            }
+        }
+        // Only checks the spec; the stand-in vault is never used
+        self.signer_backend(Arc::new(MemoryKeyStore::new()))?;
+        let api_keys = self.api_keys()?;
+        if let Some(settings) = self.tls()? {
+            tls::acceptor(&settings)?;
+        }
+        let credentials = api_keys.is_some() || self.client_certs()?.is_some();
+        match (credentials, self.auth.disabled) {
+            (false, false) => {
+                return Err("No API keys or client certificates are configured. Set API_KEYS_FILE \
+                    (or auth.keys, tls.clients), or set AUTH_DISABLED=true (auth.disabled = true) \
+                    to serve every endpoint without authentication"
+                    .to_string())
+            }
+            (true, true) => return Err("auth.disabled cannot be combined with API keys or client certificates".to_string()),
+            _ => {}
+        }
+        self.rate_limits()?;
+        self.rate_limit_store()?;
+        self.cluster()?;
+        self.rpc_upstream()?;
+        self.token_program()?;
+        self.rent()?;
+        Ok(())
+    }
+
+    pub fn bind_addr(&self) -> Result<SocketAddr, String> {
+        self.server
+            .bind
+            .parse()
+            .map_err(|_| format!("Invalid bind address: {}", self.server.bind))
+    }
+
+    pub fn health_bind_addr(&self) -> Result<Option<SocketAddr>, String> {
+        self.server
+            .health_bind
+            .as_ref()
+            .map(|bind| bind.parse().map_err(|_| format!("Invalid health bind address: {}", bind)))
+            .transpose()
+    }
+
+    pub fn log_format(&self) -> Result<LogFormat, String> {
+        match self.server.log_format.as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("Unsupported log format: {}", other)),
+        }
+    }
+
+    pub fn status_mode(&self) -> Result<StatusMode, String> {
+        StatusMode::parse(&self.server.error_status_mode)
+            .ok_or_else(|| format!("Invalid error status mode: {}", self.server.error_status_mode))
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.server.shutdown_timeout_secs)
+    }
+
+    pub fn shutdown_delay(&self) -> Duration {
+        Duration::from_secs(self.server.shutdown_delay_secs)
+    }
+
+    pub fn cors_layer(&self) -> Result<CorsLayer, String> {
+        let cors = &self.cors;
+        let any = |list: &[String]| list.iter().any(|item| item == "*");
+        if cors.allow_credentials
+            && (any(&cors.allowed_origins) || any(&cors.allowed_methods) || any(&cors.allowed_headers))
+        {
+            return Err("cors.allow_credentials cannot be combined with `*` origins, methods or headers".to_string());
+        }
+
+        let origins = if any(&cors.allowed_origins) {
+            AllowOrigin::from(Any)
+        } else {
+            let origins = cors
+                .allowed_origins
+                .iter()
+                .map(|origin| {
+                    let valid = origin.starts_with("http://") || origin.starts_with("https://");
+                    HeaderValue::from_str(origin)
+                        .ok()
+                        .filter(|_| valid && !origin.ends_with('/'))
+                        .ok_or_else(|| format!("Invalid CORS origin: {}", origin))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        };
+        let methods = if any(&cors.allowed_methods) {
+            AllowMethods::from(Any)
+        } else {
+            let methods = cors
+                .allowed_methods
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                        .map_err(|_| format!("Invalid CORS method: {}", method))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowMethods::list(methods)
+        };
+        let headers = if any(&cors.allowed_headers) {
+            AllowHeaders::from(Any)
+        } else {
+            let headers = cors
+                .allowed_headers
+                .iter()
+                .map(|name| {
+                    HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid CORS header: {}", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowHeaders::list(headers)
+        };
+
+        // Headers the server sets that browser clients need to read
+        let mut layer = CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(cors.allow_credentials)
+            .expose_headers([REQUEST_ID_HEADER.clone(), RETRY_AFTER]);
+        if let Some(secs) = cors.max_age_secs {
+            layer = layer.max_age(Duration::from_secs(secs));
+        }
+        Ok(layer)
+    }
+
+    pub fn request_limits(&self) -> Result<RequestLimits, String> {
+        let limits = &self.limits;
+        if limits.max_body_bytes == 0 {
+            return Err("limits.max_body_bytes must be greater than zero".to_string());
+        }
+        if limits.request_timeout_ms == 0 {
+            return Err("limits.request_timeout_ms must be greater than zero".to_string());
+        }
+
+        let mut request_limits =
+            RequestLimits::new(limits.max_body_bytes, Duration::from_millis(limits.request_timeout_ms));
+        for (route, timeout_ms) in &limits.route_timeouts_ms {
+            if !route.starts_with('/') || *timeout_ms == 0 {
+                return Err(format!("Invalid route timeout: {} = {}", route, timeout_ms));
+            }
+            request_limits = request_limits.with_route_timeout(route, Duration::from_millis(*timeout_ms));
+        }
+        Ok(request_limits)
+    }
+
+    pub fn vault_backend(&self) -> Result<VaultBackend, String> {
+        match self.vault.backend.as_str() {
+            "memory" => Ok(VaultBackend::Memory),
+            spec => match spec.strip_prefix("file:") {
+                Some(path) if !path.is_empty() => Ok(VaultBackend::File(PathBuf::from(path))),
+                _ => Err(format!("Unsupported key vault backend: {}", spec)),
+            },
+        }
+    }
+
+    /// Opens the configured key vault, creating a missing vault file. The file vault's
+    /// password comes from `KEY_VAULT_PASSWORD`.
+    pub fn key_vault(&self) -> Result<Arc<dyn KeyStore>, String> {
+        self.open_key_vault(std::env::var("KEY_VAULT_PASSWORD").ok())
+    }
+
+    /// Opens an existing vault file, for `--check-config` to check the password against
+    /// it. A vault file that does not exist yet is left for the server to create.
+    pub fn check_key_vault(&self) -> Result<(), String> {
+        match self.vault_backend()? {
+            VaultBackend::File(path) if path.exists() => self.key_vault().map(drop),
+            _ => Ok(()),
+        }
+    }
+
+    fn open_key_vault(&self, password: Option<String>) -> Result<Arc<dyn KeyStore>, String> {
+        Ok(match self.vault_backend()? {
+            VaultBackend::File(path) => {
+                let password = password.ok_or("KEY_VAULT_PASSWORD must be set for a file vault")?;
+                let vault = FileKeyStore::open(&path, &password)
+                    .map_err(|e| format!("Failed to open key vault {}: {}", path.display(), e))?;
+                Arc::new(vault)
+            }
+            VaultBackend::Memory => Arc::new(MemoryKeyStore::new()),
+        })
+    }
+
+    /// The signing backend: `local` signs with keys from `vault`, `unix:<path>` or
+    /// `http://host:port` forward to an external signer.
+    pub fn signer_backend(&self, vault: Arc<dyn KeyStore>) -> Result<Arc<dyn SignerBackend>, String> {
+        Ok(match self.signer.backend.as_str() {
+            "local" => Arc::new(LocalSigner::new(vault)),
+            spec => Arc::new(RemoteSigner::from_spec(spec)?),
+        })
+    }
+
+    /// Where rate limit buckets live: `memory`, or `redis://host:port` to share them
+    /// between instances.
+    pub fn rate_limit_store(&self) -> Result<Arc<dyn RateLimitStore>, String> {
+        Ok(match self.rate_limit.store.as_str() {
+            "memory" => Arc::new(MemoryRateLimitStore::new()),
+            url => Arc::new(RedisRateLimitStore::from_url(url)?),
+        })
+    }
+
+    /// Keys from the keys file and the inline list; `None` when neither is configured.
+    pub fn api_keys(&self) -> Result<Option<ApiKeys>, String> {
+        if self.auth.api_keys_file.is_none() && self.auth.keys.is_empty() {
+            return Ok(None);
+        }
+
+        let mut api_keys = match &self.auth.api_keys_file {
+            Some(path) => ApiKeys::load(path)?,
+            None => ApiKeys::default(),
+        };
+        for entry in &self.auth.keys {
+            api_keys
+                .insert(&entry.name, &entry.hash, &entry.scopes)
+                .map_err(|e| format!("auth.keys entry {}: {}", entry.name, e))?;
+        }
+        if api_keys.is_empty() {
+            return Err("API keys are configured but none are defined".to_string());
+        }
+        Ok(Some(api_keys))
+    }
+
+    /// TLS settings, or `None` to serve plain HTTP.
+    pub fn tls(&self) -> Result<Option<TlsSettings>, String> {
+        let tls = &self.tls;
+        let (cert, key) = match (&tls.cert, &tls.key) {
+            (Some(cert), Some(key)) => (cert, key),
+            (None, None) if tls.client_ca.is_none() && tls.clients.is_empty() => return Ok(None),
+            _ => return Err("tls.cert and tls.key must both be set to enable TLS".to_string()),
+        };
+        if tls.reload_interval_secs == 0 {
+            return Err("tls.reload_interval_secs must be greater than zero".to_string());
+        }
+
+        let client_auth = match (&tls.client_ca, tls.client_auth.as_str()) {
+            (None, _) if !tls.clients.is_empty() => {
+                return Err("tls.clients requires tls.client_ca".to_string());
+            }
+            (None, _) => ClientAuth::None,
+            (Some(ca), "required") => ClientAuth::Required(PathBuf::from(ca)),
+            (Some(ca), "optional") => ClientAuth::Optional(PathBuf::from(ca)),
+            (Some(_), other) => return Err(format!("Unsupported tls.client_auth: {}", other)),
+        };
+        Ok(Some(TlsSettings {
+            cert: PathBuf::from(cert),
+            key: PathBuf::from(key),
+            reload_interval: Duration::from_secs(tls.reload_interval_secs),
+            client_auth,
+        }))
+    }
+
+    /// Client certificate identities, or `None` when none are configured.
+    pub fn client_certs(&self) -> Result<Option<ApiKeys>, String> {
+        if self.tls.clients.is_empty() {
+            return Ok(None);
+        }
+        let mut client_certs = ApiKeys::default();
+        for entry in &self.tls.clients {
+            let fingerprint = entry.fingerprint.replace(':', "");
+            client_certs
+                .insert(&entry.name, &fingerprint, &entry.scopes)
+                .map_err(|e| format!("tls.clients entry {}: {}", entry.name, e))?;
+        }
+        Ok(Some(client_certs))
+    }
+
+    /// `(default, expensive)` limits, or `None` when rate limiting is off.
+    pub fn rate_limits(&self) -> Result<Option<(Limit, Limit)>, String> {
+        let Some(default) = &self.rate_limit.default else {
+            return Ok(None);
+        };
+        Ok(Some((Limit::parse(default)?, Limit::parse(&self.rate_limit.expensive)?)))
+    }
+
+    pub fn cluster(&self) -> Result<Cluster, String> {
+        match self.solana.cluster.as_str() {
+            "mainnet" | "mainnet-beta" => Ok(Cluster::Mainnet),
+            "devnet" => Ok(Cluster::Devnet),
+            "testnet" => Ok(Cluster::Testnet),
+            "localnet" => Ok(Cluster::Localnet),
+            other => Err(format!("Unknown cluster: {}", other)),
+        }
+    }
+
+    pub fn rpc_upstream(&self) -> Result<Option<RpcUpstream>, String> {
+        self.solana.rpc_url.as_deref().map(RpcUpstream::from_url).transpose()
+    }
+
+    pub fn token_program(&self) -> Result<Pubkey, String> {
+        let token_2022: Pubkey = TOKEN_2022_PROGRAM_ID.parse().expect("valid program id");
+        let program = match self.solana.token_program.as_str() {
+            "spl-token" => spl_token::id(),
+            "token-2022" => token_2022,
+            other => other.parse().map_err(|_| format!("Invalid token program: {}", other))?,
+        };
+        if program != spl_token::id() && program != token_2022 {
+            return Err(format!("Unsupported token program: {}", program));
+        }
+        Ok(program)
+    }
+
+    pub fn rent(&self) -> Result<Rent, String> {
+        if !(self.rent.exemption_threshold.is_finite() && self.rent.exemption_threshold > 0.0) {
+            return Err("rent.exemption_threshold must be a positive number".to_string());
+        }
+        Ok(Rent {
+            lamports_per_byte_year: self.rent.lamports_per_byte_year,
+            exemption_threshold: self.rent.exemption_threshold,
+            ..Rent::default()
+        })
+    }
+}
+
+// Boolean environment variables are `true` or `false`; anything else is a typo to report
+fn env_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("Invalid {}: {} (expected true or false)", name, value)),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn with_env(config: &mut Config, vars: &[(&str, &str)]) -> Result<(), String> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        config.apply_env(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let mut config = Config::parse(
+            r#"
+            [server]
+            bind = "127.0.0.1:4000"
+            log_format = "json"
+
+            [rate_limit]
+            default = "10/s"
+            "#,
+        )
+        .unwrap();
+        with_env(
+            &mut config,
+            &[("PORT", "5000"), ("RATE_LIMIT", "99/m"), ("DISABLED_FEATURES", "rpc, docs"), ("AUTH_DISABLED", "true")],
+        )
+        .unwrap();
+
+        assert_eq!(config.server.bind, "127.0.0.1:5000");
+        assert_eq!(config.server.log_format, "json");
+        assert_eq!(config.rate_limit.default.as_deref(), Some("99/m"));
+        assert!(!config.features.enabled("/rpc"));
+        assert!(config.features.enabled("/keypair"));
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn unset_variables_keep_the_file_values() {
+        let mut config = Config::parse("[limits]\nmax_body_bytes = 1024\n").unwrap();
+        with_env(&mut config, &[]).unwrap();
+        assert_eq!(config.limits.max_body_bytes, 1024);
+        assert_eq!(config.server.bind, ServerConfig::default().bind);
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        for contents in [
+            "[server]\nunknown = 1\n",
+            "[nope]\n",
+            "[limits]\nmax_body_bytes = \"big\"\n",
+        ] {
+            assert!(Config::parse(contents).is_err(), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn invalid_environment_values_are_rejected() {
+        for (name, value) in [
+            ("SHUTDOWN_TIMEOUT_SECS", "soon"),
+            ("SHUTDOWN_DELAY_SECS", "-5"),
+            ("MAX_BODY_BYTES", "-1"),
+            ("REQUEST_TIMEOUT_MS", "1.5"),
+            ("DISABLED_FEATURES", "teleport"),
+            ("COMPRESSION", "off"),
+            ("CORS_ALLOW_CREDENTIALS", "yes"),
+            ("AUTH_DISABLED", "1"),
+            ("RATE_LIMIT_TRUST_PROXY", "TRUE"),
+        ] {
+            assert!(with_env(&mut Config::default(), &[(name, value)]).is_err(), "{}={}", name, value);
+        }
+    }
+
+    #[test]
+    fn boolean_variables_take_true_or_false() {
+        let mut config = Config::default();
+        let vars = [("COMPRESSION", "false"), ("CORS_ALLOW_CREDENTIALS", "true"), ("AUTH_DISABLED", "true"), ("RATE_LIMIT_TRUST_PROXY", "true")];
+        with_env(&mut config, &vars).unwrap();
+        assert!(!config.server.compression);
+        assert!(config.cors.allow_credentials && config.auth.disabled && config.rate_limit.trust_proxy);
+
+        let error = with_env(&mut config, &[("AUTH_DISABLED", "ture")]).unwrap_err();
+        assert_eq!(error, "Invalid AUTH_DISABLED: ture (expected true or false)");
+    }
+
+    #[test]
+    fn backends_report_bad_settings_as_errors() {
+        let dir = std::env::temp_dir().join(format!("solana-http-server-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut config = Config::default();
+        config.vault.backend = format!("file:{}", dir.join("vault.json").display());
+
+        let missing = config.open_key_vault(None).err().unwrap();
+        assert!(missing.contains("KEY_VAULT_PASSWORD"), "{}", missing);
+        config.open_key_vault(Some("right".to_string())).unwrap();
+        let wrong = config.open_key_vault(Some("wrong".to_string())).err().unwrap();
+        assert!(wrong.contains("wrong vault password"), "{}", wrong);
+        let _ = fs::remove_dir_all(dir);
+
+        config.signer.backend = "carrier-pigeon".to_string();
+        assert!(config.signer_backend(Arc::new(MemoryKeyStore::new())).is_err());
+        config.rate_limit.store = "redis://".to_string();
+        assert!(config.rate_limit_store().is_err());
+
+        config.auth.disabled = true;
+        config.vault.backend = "memory".to_string();
+        assert!(config.validate().is_err());
+        config.signer.backend = "local".to_string();
+        assert!(config.validate().is_err());
+        config.rate_limit.store = "memory".to_string();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn invalid_settings_fail_validation() {
+        for vars in [
+            [("BIND_ADDRESS", "localhost")],
+            [("LOG_FORMAT", "xml")],
+            [("ERROR_STATUS_MODE", "loud")],
+            [("SHUTDOWN_TIMEOUT_SECS", "0")],
+            [("CORS_ORIGINS", "example.com")],
+            [("RATE_LIMIT", "10/d")],
+            [("RATE_LIMIT_STORE", "memcached://cache")],
+            [("SOLANA_CLUSTER", "moon")],
+            [("TOKEN_PROGRAM", "11111111111111111111111111111111")],
+            [("TLS_CERT", "cert.pem")],
+            [("KEY_VAULT", "s3:bucket")],
+            [("HEALTH_BIND_ADDRESS", "3001")],
+            [("HEALTH_BIND_ADDRESS", "0.0.0.0:3000")],
+        ] {
+            let mut config = Config::default();
+            config.auth.disabled = true;
+            with_env(&mut config, &vars).unwrap();
+            assert!(config.validate().is_err(), "{:?}", vars);
+        }
+
+        let credentials = Config::parse("[auth]\ndisabled = true\n[cors]\nallow_credentials = true\n").unwrap();
+        assert!(credentials.validate().is_err());
+    }
+
+    #[test]
+    fn authentication_is_required_unless_disabled() {
+        let open = Config::default().validate().unwrap_err();
+        assert!(open.contains("auth.disabled"), "{}", open);
+
+        let mut config = Config::default();
+        with_env(&mut config, &[("AUTH_DISABLED", "true")]).unwrap();
+        config.validate().unwrap();
+
+        let keys = r#"
+            [[auth.keys]]
+            name = "backend"
+            hash = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+            scopes = "*"
+        "#;
+        Config::parse(keys).unwrap().validate().unwrap();
+        let both = Config::parse(&format!("[auth]\ndisabled = true\n{}", keys)).unwrap();
+        assert!(both.validate().is_err());
+    }
+}
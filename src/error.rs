@@ -0,0 +1,384 @@
+use std::fmt;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_path_to_error::Segment;
+use utoipa::{IntoResponses, ToSchema};
+use crate::models::responses::ApiResponse;
+use crate::signer::SignerError;
+use crate::vault::VaultError;
+
+/// Stable, machine-readable error codes. The `error` message next to them is for humans
+/// and may change; clients should branch on the code.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidJson,
+    MissingField,
+    InvalidField,
+    InvalidRequest,
+    NotFound,
+    MethodNotAllowed,
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    PayloadTooLarge,
+    Timeout,
+    KeyNotFound,
+    KeyDisabled,
+    SignerRejected,
+    SignerUnavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidJson => "INVALID_JSON",
+            ErrorCode::MissingField => "MISSING_FIELD",
+            ErrorCode::InvalidField => "INVALID_FIELD",
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::MethodNotAllowed => "METHOD_NOT_ALLOWED",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::KeyNotFound => "KEY_NOT_FOUND",
+            ErrorCode::KeyDisabled => "KEY_DISABLED",
+            ErrorCode::SignerRejected => "SIGNER_REJECTED",
+            ErrorCode::SignerUnavailable => "SIGNER_UNAVAILABLE",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidJson | ErrorCode::MissingField => StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidField | ErrorCode::InvalidRequest | ErrorCode::SignerRejected => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ErrorCode::NotFound | ErrorCode::KeyNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden | ErrorCode::KeyDisabled => StatusCode::FORBIDDEN,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::SignerUnavailable | ErrorCode::Timeout => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Location of a JSON parse failure in the request body.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ParseErrorDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub field: Option<String>,
+    pub details: Option<ParseErrorDetails>,
+}
+
+pub type ApiResult<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+/// Error statuses as documented in the OpenAPI spec. They are only sent with
+/// `X-Error-Mode: http`; otherwise the same bodies arrive with HTTP 200.
+#[derive(IntoResponses)]
+pub enum ApiErrorResponses {
+    /// Malformed JSON or a missing required field
+    #[response(status = 400)]
+    BadRequest(ApiResponse<serde_json::Value>),
+    /// Missing or invalid API key, or an invalid SIWS challenge or signature
+    #[response(status = 401)]
+    Unauthorized(ApiResponse<serde_json::Value>),
+    /// The API key lacks the endpoint's scope, or the vault key is disabled
+    #[response(status = 403)]
+    Forbidden(ApiResponse<serde_json::Value>),
+    /// Unknown endpoint or vault key
+    #[response(status = 404)]
+    NotFound(ApiResponse<serde_json::Value>),
+    /// The request body exceeds the configured limit
+    #[response(status = 413)]
+    PayloadTooLarge(ApiResponse<serde_json::Value>),
+    /// A field has an invalid value, or the signer rejected the request
+    #[response(status = 422)]
+    UnprocessableEntity(ApiResponse<serde_json::Value>),
+    /// Too many requests; see the `Retry-After` header
+    #[response(status = 429)]
+    TooManyRequests(ApiResponse<serde_json::Value>),
+    /// The external signer is unreachable, or the request timed out
+    #[response(status = 503)]
+    ServiceUnavailable(ApiResponse<serde_json::Value>),
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            field: None,
+            details: None,
+        }
+    }
+
+    pub fn missing_field(field: impl Into<String>) -> Self {
+        Self::new(ErrorCode::MissingField, "Missing required fields").with_field(field)
+    }
+
+    pub fn invalid_field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidField, message).with_field(field)
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Maps a JSON deserialization failure, keeping the serde path and position.
+    pub fn from_json(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = known_path(err.path());
+        let inner = err.inner();
+        let reason = strip_position(&inner.to_string());
+
+        let field_path = |name: &str| match &path {
+            Some(parent) => format!("{}.{}", parent, name),
+            None => name.to_string(),
+        };
+
+        let mut error = match inner.classify() {
+            serde_json::error::Category::Data => match (missing_field_name(&reason), &path) {
+                (Some(name), _) => Self::missing_field(field_path(name)),
+                (None, Some(path)) => Self::invalid_field(path.clone(), format!("Invalid value for field `{}`", path)),
+                (None, None) => Self::invalid_request("Invalid request body"),
+            },
+            _ => Self::new(ErrorCode::InvalidJson, "Invalid JSON body"),
+        };
+
+        error.details = Some(ParseErrorDetails {
+            path,
+            line: inner.line(),
+            column: inner.column(),
+            reason,
+        });
+        error
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.code.status();
+        let code = self.code;
+
+        let mut response = (status, Json(ApiResponse::<()>::from_error(self))).into_response();
+        // Lets the status-mode layer recognise API errors and downgrade them when needed
+        response.extensions_mut().insert(code);
+        response
+    }
+}
+
+impl From<VaultError> for ApiError {
+    fn from(err: VaultError) -> Self {
+        let code = match err {
+            VaultError::NotFound => ErrorCode::KeyNotFound,
+            VaultError::Disabled => ErrorCode::KeyDisabled,
+            VaultError::Storage(_) => ErrorCode::Internal,
+        };
+        Self::new(code, err.to_string())
+    }
+}
+
+impl From<SignerError> for ApiError {
+    fn from(err: SignerError) -> Self {
+        let code = match err {
+            SignerError::Rejected(_) => ErrorCode::SignerRejected,
+            SignerError::Unavailable(_) => ErrorCode::SignerUnavailable,
+        };
+        Self::new(code, err.to_string())
+    }
+}
+
+/// Attaches a field name to the plain `String` errors returned by the validation helpers.
+pub trait FieldResult<T> {
+    fn field(self, field: &str) -> Result<T, ApiError>;
+}
+
+impl<T> FieldResult<T> for Result<T, String> {
+    fn field(self, field: &str) -> Result<T, ApiError> {
+        self.map_err(|e| ApiError::invalid_field(field, e))
+    }
+}
+
+pub fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(deserializer).map_err(ApiError::from_json)?;
+    Ok(value)
+}
+
+pub fn parse_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, ApiError> {
+    // Re-serializing keeps line/column reporting consistent with body parsing
+    let raw = serde_json::to_vec(&value).map_err(|e| ApiError::internal(e.to_string()))?;
+    parse_json(&raw)
+}
+
+// The serde path up to its first segment serde could not name (shown as `?`, e.g. inside
+// flattened or untagged types); `None` when no segment is known
+fn known_path(path: &serde_path_to_error::Path) -> Option<String> {
+    let mut known = String::new();
+    for segment in path.iter() {
+        let name = match segment {
+            Segment::Seq { index } => {
+                known.push_str(&format!("[{}]", index));
+                continue;
+            }
+            Segment::Map { key } => key.as_str(),
+            Segment::Enum { variant } => variant.as_str(),
+            Segment::Unknown => break,
+        };
+        if !known.is_empty() {
+            known.push('.');
+        }
+        known.push_str(name);
+    }
+    (!known.is_empty()).then_some(known)
+}
+
+fn missing_field_name(reason: &str) -> Option<&str> {
+    reason
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next())
+}
+
+// serde_json appends " at line X column Y"; position is reported separately
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use axum::body::{self, Body};
+    use axum::extract::Request;
+    use axum::http::header::CONTENT_TYPE;
+    use axum::middleware;
+    use serde_json::{json, Value};
+    use tower::Service;
+    use crate::middleware::status_mode::{apply_status_mode, StatusMode, ERROR_MODE_HEADER};
+    use crate::routes;
+    use crate::signer::LocalSigner;
+    use crate::state::AppState;
+    use crate::vault::MemoryKeyStore;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Inner {
+        amount: u64,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Outer {
+        items: Vec<Inner>,
+        #[serde(flatten)]
+        rest: Inner,
+    }
+
+    fn details(error: &ApiError) -> Value {
+        serde_json::to_value(error.details.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_errors_report_the_serde_path() {
+        let error = parse_json::<Outer>(br#"{"items": [{"amount": 1}, {}], "amount": 2}"#).unwrap_err();
+        assert_eq!(error.code, ErrorCode::MissingField);
+        assert_eq!(error.field.as_deref(), Some("items[1].amount"));
+
+        let error = parse_json::<Outer>(br#"{"items": [{"amount": "1"}], "amount": 2}"#).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidField);
+        assert_eq!(details(&error)["path"], "items[0].amount");
+
+        let error = parse_json::<Outer>(b"{\"items\": [}").unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidJson);
+        assert_eq!(details(&error)["line"], 1);
+    }
+
+    #[test]
+    fn unknown_paths_are_omitted() {
+        // Flattened fields are deserialized without a path serde can name
+        let error = parse_json::<Outer>(br#"{"items": [], "amount": "2"}"#).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+        assert!(error.field.is_none());
+        assert!(details(&error).get("path").is_none(), "{}", details(&error));
+    }
+
+    async fn post(mode: StatusMode, header: Option<&str>, body: &str) -> (StatusCode, Value) {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault))).with_status_mode(mode);
+        let mut app = routes::versioned_router(routes::endpoints)
+            .layer(middleware::from_fn_with_state(state.clone(), apply_status_mode))
+            .with_state(state);
+
+        let mut request = Request::post("/token/create").header(CONTENT_TYPE, "application/json");
+        if let Some(header) = header {
+            request = request.header(&ERROR_MODE_HEADER, header);
+        }
+        let response = app.call(request.body(Body::from(body.to_string())).unwrap()).await.unwrap_or_else(|e| match e {});
+        let status = response.status();
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn error_envelope_in_both_status_modes() {
+        let body = r#"{"mint": "x", "decimals": 6}"#;
+        let expected = json!({
+            "success": false,
+            "error": "Missing required fields",
+            "code": "MISSING_FIELD",
+            "field": "payer",
+            "details": { "line": 1, "column": 28, "reason": "missing field `payer`" },
+        });
+
+        for (mode, header, status) in [
+            (StatusMode::Legacy, None, StatusCode::OK),
+            (StatusMode::Http, None, StatusCode::BAD_REQUEST),
+            (StatusMode::Legacy, Some("http"), StatusCode::BAD_REQUEST),
+            (StatusMode::Http, Some("legacy"), StatusCode::OK),
+        ] {
+            let (actual_status, envelope) = post(mode, header, body).await;
+            assert_eq!(actual_status, status, "{:?} {:?}", mode, header);
+            assert_eq!(envelope, expected, "{:?} {:?}", mode, header);
+        }
+    }
+}
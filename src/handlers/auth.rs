@@ -0,0 +1,144 @@
+use axum::{extract::State, response::Json, body::Bytes};
+use crate::auth::siws::{is_statement, SiwsMessage};
+use crate::error::{parse_json, ApiError, ApiErrorResponses, ApiResult, ErrorCode, FieldResult};
+use crate::handlers::message::prepare_verification;
+use crate::metrics::metrics;
+use crate::models::requests::{SiwsChallengeRequest, SiwsSessionRequest, SiwsVerifyRequest, VerifyMessageRequest};
+use crate::models::responses::{ApiResponse, SiwsChallengeResponse, SiwsSessionResponse};
+use crate::state::AppState;
+use crate::utils::validation::validate_pubkey;
+
+#[utoipa::path(
+    post,
+    path = "/auth/siws/challenge",
+    tag = "auth",
+    summary = "Issue a Sign-In With Solana challenge",
+    request_body = SiwsChallengeRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<SiwsChallengeResponse>), ApiErrorResponses),
+)]
+pub async fn siws_challenge(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> ApiResult<SiwsChallengeResponse> {
+    // Parse JSON manually
+    let request: SiwsChallengeRequest = parse_json(&body)?;
+
+    if request.domain.is_empty() {
+        return Err(ApiError::missing_field("domain"));
+    }
+
+    validate_pubkey(&request.address).field("address")?;
+
+    // Every field occupies exactly one line of the signed message
+    let single_line = |v: &Option<String>| !matches!(v.as_deref(), Some(v) if v.is_empty() || v.contains('\n'));
+    let invalid = [
+        ("domain", request.domain.contains(char::is_whitespace)),
+        ("statement", request.statement.as_deref().is_some_and(|statement| !is_statement(statement))),
+        ("uri", !single_line(&request.uri)),
+        ("chainId", !single_line(&request.chain_id)),
+    ];
+    if let Some((field, _)) = invalid.iter().find(|(_, invalid)| *invalid) {
+        return Err(ApiError::invalid_field(*field, "Invalid SIWS field"));
+    }
+
+    let message = state.siws.issue_challenge(
+        request.domain,
+        request.address,
+        request.statement,
+        request.uri,
+        request.chain_id.or_else(|| Some(state.cluster.as_str().to_string())),
+    );
+
+    let response = SiwsChallengeResponse {
+        message: message.to_text(),
+        nonce: message.nonce,
+        issued_at: message.issued_at,
+        expiration_time: message.expiration_time,
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/siws/verify",
+    tag = "auth",
+    summary = "Verify a signed SIWS challenge and open a session",
+    request_body = SiwsVerifyRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<SiwsSessionResponse>), ApiErrorResponses),
+)]
+pub async fn siws_verify(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> ApiResult<SiwsSessionResponse> {
+    // Parse JSON manually
+    let request: SiwsVerifyRequest = parse_json(&body)?;
+
+    let message = SiwsMessage::parse(&request.message).field("message")?;
+
+    state
+        .siws
+        .check_challenge(&message, &request.message)
+        .map_err(|e| ApiError::new(ErrorCode::Unauthorized, e))?;
+
+    // Same checks as /message/verify, with the signer being the address in the message
+    let prepared = prepare_verification(VerifyMessageRequest {
+        message: request.message.clone(),
+        signature: request.signature,
+        pubkey: message.address.clone(),
+        format: Default::default(),
+        encoding: Default::default(),
+        signature_encoding: request.signature_encoding,
+    })?;
+
+    let is_valid = prepared.is_valid();
+    metrics().signature_verified(is_valid);
+    if !is_valid {
+        return Err(ApiError::new(ErrorCode::Unauthorized, "Invalid signature"));
+    }
+
+    // Fails when a concurrent sign-in used the same challenge first
+    state
+        .siws
+        .take_challenge(&message, &request.message)
+        .map_err(|e| ApiError::new(ErrorCode::Unauthorized, e))?;
+
+    let (token, expires_at) = state.siws.create_session(message.address.clone());
+
+    let response = SiwsSessionResponse {
+        token,
+        address: message.address,
+        expires_at: expires_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/siws/session",
+    tag = "auth",
+    summary = "Look up the address signed in with a session token",
+    request_body = SiwsSessionRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<SiwsSessionResponse>), ApiErrorResponses),
+)]
+pub async fn siws_session(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> ApiResult<SiwsSessionResponse> {
+    // Parse JSON manually
+    let request: SiwsSessionRequest = parse_json(&body)?;
+
+    let session = state
+        .siws
+        .session(&request.token)
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "Unknown or expired session"))?;
+
+    let response = SiwsSessionResponse {
+        token: request.token,
+        address: session.address,
+        expires_at: session.expires_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
@@ -0,0 +1,57 @@
+use std::sync::OnceLock;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Json, Response};
+use utoipa::OpenApi;
+use crate::openapi::ApiDoc;
+
+// Embedded by build.rs from assets/redoc.standalone.js; empty when the build had none
+const REDOC_BUNDLE: &str = include_str!(concat!(env!("OUT_DIR"), "/redoc.standalone.js"));
+
+const DOCS_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Solana HTTP Server API</title>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+  </head>
+  <body>
+    <redoc spec-url="/openapi.json"></redoc>
+    <script src="/docs/redoc.standalone.js"></script>
+  </body>
+</html>
+"#;
+
+const SPEC_LINK_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Solana HTTP Server API</title>
+    <meta charset="utf-8" />
+  </head>
+  <body>
+    <p>This build does not include the documentation viewer. The API is described by
+    <a href="/openapi.json">/openapi.json</a>.</p>
+  </body>
+</html>
+"#;
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    // The spec is fixed at build time, generate it once
+    static SPEC: OnceLock<utoipa::openapi::OpenApi> = OnceLock::new();
+    Json(SPEC.get_or_init(ApiDoc::openapi).clone())
+}
+
+pub async fn docs_page() -> Html<&'static str> {
+    Html(if REDOC_BUNDLE.is_empty() { SPEC_LINK_PAGE } else { DOCS_PAGE })
+}
+
+pub async fn redoc_bundle() -> Response {
+    if REDOC_BUNDLE.is_empty() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    (
+        [(CONTENT_TYPE, "application/javascript; charset=utf-8"), (CACHE_CONTROL, "public, max-age=86400")],
+        REDOC_BUNDLE,
+    )
+        .into_response()
+}
@@ -0,0 +1,183 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use crate::models::responses::{
+    ApiResponse, DependencyCheck, HealthResponse, ReadinessResponse, VersionResponse,
+};
+use crate::state::AppState;
+use crate::vault;
+
+/// Liveness: the process is up and serving requests. Never checks dependencies, so a
+/// flaky upstream cannot get the container restarted.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "operations",
+    summary = "Liveness probe",
+    responses((status = 200, description = "The server is running", body = ApiResponse<HealthResponse>)),
+)]
+pub async fn healthz() -> Json<ApiResponse<HealthResponse>> {
+    Json(ApiResponse::success(HealthResponse { status: "ok".to_string() }))
+}
+
+/// Readiness: every dependency the server needs answers and the server is not shutting
+/// down. Always sends a real 503 when
+/// not ready, whatever the error status mode, since probes only look at the status.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "operations",
+    summary = "Readiness probe",
+    responses(
+        (status = 200, description = "All dependencies are reachable", body = ApiResponse<ReadinessResponse>),
+        (status = 503, description = "A dependency is unreachable or the server is shutting down", body = ApiResponse<ReadinessResponse>),
+    ),
+)]
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ApiResponse<ReadinessResponse>>) {
+    let mut checks = Vec::new();
+    if state.is_draining() {
+        checks.push(dependency("shutdown", Err("server is draining".to_string())));
+    }
+    checks.extend([
+        dependency("vault", vault::blocking(&state.vault, |vault| vault.health()).await.map_err(|e| e.to_string())),
+        dependency("signer", state.signer.health().await.map_err(|e| e.to_string())),
+    ]);
+    if let Some(rpc) = &state.rpc {
+        checks.push(dependency("rpc", rpc.health().await.map_err(|e| e.to_string())));
+    }
+
+    let ready = checks.iter().all(|check| check.healthy);
+    let report = ReadinessResponse { ready, checks };
+    if ready {
+        return (StatusCode::OK, Json(ApiResponse::success(report)));
+    }
+
+    let mut response = ApiResponse::success(report);
+    response.success = false;
+    response.error = Some("Service not ready".to_string());
+    (StatusCode::SERVICE_UNAVAILABLE, Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "operations",
+    summary = "Build information",
+    responses((status = 200, description = "Version, git hash, enabled endpoint groups and dependency versions", body = ApiResponse<VersionResponse>)),
+)]
+pub async fn version(State(state): State<AppState>) -> Json<ApiResponse<VersionResponse>> {
+    let features = state.features.enabled_groups().into_iter().map(str::to_string).collect();
+
+    Json(ApiResponse::success(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: env!("BUILD_GIT_HASH").to_string(),
+        features,
+        solana_sdk: env!("BUILD_SOLANA_SDK_VERSION").to_string(),
+        spl_token: env!("BUILD_SPL_TOKEN_VERSION").to_string(),
+    }))
+}
+
+fn dependency(name: &str, result: Result<(), String>) -> DependencyCheck {
+    DependencyCheck {
+        name: name.to_string(),
+        healthy: result.is_ok(),
+        error: result.err(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use axum::body::{self, Body};
+    use axum::extract::Request;
+    use serde_json::Value;
+    use tower::Service;
+    use crate::config::FeaturesConfig;
+    use crate::routes;
+    use crate::signer::{LocalSigner, RemoteSigner};
+    use crate::upstream::RpcUpstream;
+    use crate::vault::{FileKeyStore, MemoryKeyStore};
+
+    fn healthy_state() -> AppState {
+        let vault = Arc::new(MemoryKeyStore::new());
+        AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+    }
+
+    async fn get(state: AppState, path: &str) -> (StatusCode, Value) {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        let response = routes::versioned_router(routes::endpoints)
+            .with_state(state)
+            .call(request)
+            .await
+            .unwrap_or_else(|e| match e {});
+        let status = response.status();
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    // The names of the failing checks in a `/readyz` answer
+    fn failing(body: &Value) -> Vec<&str> {
+        body["data"]["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|check| check["healthy"] == false)
+            .map(|check| check["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn ready_when_every_dependency_answers() {
+        let (status, body) = get(healthy_state(), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["ready"], true);
+        assert!(failing(&body).is_empty());
+    }
+
+    #[tokio::test]
+    async fn unreachable_dependencies_are_not_ready() {
+        let dir = std::env::temp_dir().join(format!("solana-http-server-readyz-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let vault = Arc::new(FileKeyStore::open(dir.join("vault.json"), "pw").unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        let (status, body) = get(state, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(failing(&body), ["vault"]);
+
+        let signer = RemoteSigner::unix(std::env::temp_dir().join("solana-http-server-no-such-signer.sock"));
+        let state = AppState::new(Arc::new(MemoryKeyStore::new()), Arc::new(signer));
+        let (status, body) = get(state, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(failing(&body), ["signer"]);
+
+        let state = healthy_state().with_rpc_upstream(RpcUpstream::from_url("http://127.0.0.1:1").unwrap());
+        let (status, body) = get(state, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["success"], false);
+        assert_eq!(failing(&body), ["rpc"]);
+    }
+
+    #[tokio::test]
+    async fn draining_flips_readiness_but_not_liveness() {
+        let state = healthy_state();
+        assert_eq!(get(state.clone(), "/readyz").await.0, StatusCode::OK);
+
+        state.start_draining();
+        let (status, body) = get(state.clone(), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(failing(&body), ["shutdown"]);
+        assert_eq!(get(state, "/healthz").await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn version_reports_the_enabled_endpoint_groups() {
+        let (_, body) = get(healthy_state(), "/version").await;
+        assert_eq!(body["data"]["features"], serde_json::json!(FeaturesConfig::GROUPS));
+
+        let features = FeaturesConfig { rpc: false, docs: false, ..FeaturesConfig::default() };
+        let (_, body) = get(healthy_state().with_features(features), "/version").await;
+        let features = body["data"]["features"].as_array().unwrap();
+        assert_eq!(features.len(), FeaturesConfig::GROUPS.len() - 2);
+        assert!(!features.contains(&Value::from("rpc")) && !features.contains(&Value::from("docs")));
+    }
+}
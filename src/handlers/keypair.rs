@@ -1,14 +1,255 @@
-use axum::response::Json;
+use axum::{extract::State, response::Json, body::Bytes};
 use solana_sdk::signer::{keypair::Keypair, Signer};
-use crate::models::responses::{ApiResponse, KeypairResponse};
+use solana_sdk::signer::keypair::keypair_from_seed;
+use crate::error::{parse_json, ApiError, ApiErrorResponses, ApiResult, FieldResult};
+use crate::metrics::metrics;
+use crate::models::requests::{CombineSharesRequest, DecryptKeystoreRequest, KeypairRequest, SplitKeypairRequest};
+use crate::models::responses::{ApiResponse, KeypairResponse, SplitKeypairResponse};
+use crate::state::AppState;
+use crate::utils::keystore::{decrypt_keypair, encrypt_keypair};
+use crate::utils::shamir;
+use crate::utils::validation::validate_pubkey;
+use crate::vault;
+use zeroize::Zeroizing;
+
+#[utoipa::path(
+    post,
+    path = "/keypair",
+    tag = "keypair",
+    summary = "Generate a keypair; the body is optional",
+    request_body = Option<KeypairRequest>,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<KeypairResponse>), ApiErrorResponses),
+)]
+pub async fn generate_keypair(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> ApiResult<KeypairResponse> {
+    // The body is optional; an empty body keeps the plain base58 response
+    let request: KeypairRequest = if body.is_empty() {
+        KeypairRequest::default()
+    } else {
+        parse_json(&body)?
+    };
+
+    // Vault-held keys never leave the server, so there is no keystore to return
+    if request.store && request.password.is_some() {
+        return Err(ApiError::invalid_field("password", "A stored key cannot also be returned as a keystore"));
+    }
 
-pub async fn generate_keypair() -> Json<ApiResponse<KeypairResponse>> {
     let keypair = Keypair::new();
-    
+
+    // Vault-held keys never leave the server; only the handle is returned
+    if request.store {
+        let label = request.label;
+        let record = vault::blocking(&state.vault, move |vault| vault.insert(&keypair, label)).await?;
+        metrics().keypair_generated("vault");
+        return Ok(Json(ApiResponse::success(KeypairResponse {
+            pubkey: record.pubkey,
+            secret: None,
+            keystore: None,
+            key_id: Some(record.key_id),
+        })));
+    }
+
+    let password = match request.password {
+        Some(password) => password,
+        None => {
+            metrics().keypair_generated("plain");
+            let response = KeypairResponse {
+                pubkey: keypair.pubkey().to_string(),
+                secret: Some(encode_secret(&keypair)),
+                keystore: None,
+                key_id: None,
+            };
+            return Ok(Json(ApiResponse::success(response)));
+        }
+    };
+
+    // Key derivation is deliberately slow, keep it off the async workers
+    let (pubkey, keystore) = tokio::task::spawn_blocking(move || {
+        encrypt_keypair(&keypair, &password).map(|keystore| (keypair.pubkey(), keystore))
+    })
+    .await
+    .map_err(|_| ApiError::internal("Failed to encrypt keystore"))?
+    .field("password")?;
+    metrics().keypair_generated("keystore");
+
+    Ok(Json(ApiResponse::success(KeypairResponse {
+        pubkey: pubkey.to_string(),
+        secret: None,
+        keystore: Some(keystore),
+        key_id: None,
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/keypair/decrypt",
+    tag = "keypair",
+    summary = "Decrypt a keystore into a base58 secret",
+    request_body = DecryptKeystoreRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<KeypairResponse>), ApiErrorResponses),
+)]
+pub async fn decrypt_keystore(
+    body: Bytes,
+) -> ApiResult<KeypairResponse> {
+    // Parse JSON manually
+    let request: DecryptKeystoreRequest = parse_json(&body)?;
+
+    let keypair = tokio::task::spawn_blocking(move || {
+        decrypt_keypair(&request.keystore, &request.password)
+    })
+    .await
+    .map_err(|_| ApiError::internal("Failed to decrypt keystore"))?
+    .field("keystore")?;
+
+    let response = KeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        secret: Some(encode_secret(&keypair)),
+        keystore: None,
+        key_id: None,
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/keypair/split",
+    tag = "keypair",
+    summary = "Split a secret into Shamir shares",
+    request_body = SplitKeypairRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<SplitKeypairResponse>), ApiErrorResponses),
+)]
+pub async fn split_keypair(
+    body: Bytes,
+) -> ApiResult<SplitKeypairResponse> {
+    // Parse JSON manually
+    let request: SplitKeypairRequest = parse_json(&body)?;
+
+    if request.secret.is_empty() {
+        return Err(ApiError::missing_field("secret"));
+    }
+
+    let keypair_bytes = bs58::decode(request.secret.expose())
+        .into_vec()
+        .map(Zeroizing::new)
+        .map_err(|_| ApiError::invalid_field("secret", "Invalid secret key format"))?;
+
+    let keypair = Keypair::from_bytes(&keypair_bytes)
+        .map_err(|_| ApiError::invalid_field("secret", "Invalid secret key"))?;
+
+    // Only the 32-byte seed is shared; the public half is derived again on combine
+    let shares = shamir::split(keypair.secret().as_bytes(), request.shares, request.threshold)
+        .map_err(ApiError::invalid_request)?;
+
+    let response = SplitKeypairResponse {
+        pubkey: keypair.pubkey().to_string(),
+        threshold: request.threshold,
+        shares: shares.iter().map(|s| bs58::encode(s).into_string()).collect(),
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/keypair/combine",
+    tag = "keypair",
+    summary = "Recombine Shamir shares into a keypair",
+    request_body = CombineSharesRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<KeypairResponse>), ApiErrorResponses),
+)]
+pub async fn combine_shares(
+    body: Bytes,
+) -> ApiResult<KeypairResponse> {
+    // Parse JSON manually
+    let request: CombineSharesRequest = parse_json(&body)?;
+
+    let expected = validate_pubkey(&request.pubkey).field("pubkey")?;
+
+    let shares = request
+        .shares
+        .iter()
+        .map(|s| bs58::decode(s.expose()).into_vec())
+        .collect::<Result<Vec<Vec<u8>>, _>>()
+        .map(Zeroizing::new)
+        .map_err(|_| ApiError::invalid_field("shares", "Invalid share format"))?;
+
+    let seed = shamir::combine(&shares).map(Zeroizing::new).field("shares")?;
+
+    // A wrong or insufficient set of shares still interpolates to *some* value
+    let keypair = match keypair_from_seed(&seed) {
+        Ok(kp) if kp.pubkey() == expected => kp,
+        _ => {
+            return Err(ApiError::invalid_field(
+                "shares",
+                "Recovered key does not match the expected public key",
+            ))
+        }
+    };
+
     let response = KeypairResponse {
         pubkey: keypair.pubkey().to_string(),
-        secret: bs58::encode(keypair.to_bytes()).into_string(),
+        secret: Some(encode_secret(&keypair)),
+        keystore: None,
+        key_id: None,
     };
-    
-    Json(ApiResponse::success(response))
-} 
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// The intermediate byte array is wiped; the returned string belongs to the response
+fn encode_secret(keypair: &Keypair) -> String {
+    let bytes = Zeroizing::new(keypair.to_bytes());
+    bs58::encode(&*bytes).into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use serde_json::{json, Value};
+    use crate::error::ErrorCode;
+    use crate::signer::LocalSigner;
+    use crate::vault::{KeyStore, MemoryKeyStore};
+
+    fn state() -> (AppState, Arc<MemoryKeyStore>) {
+        let vault = Arc::new(MemoryKeyStore::new());
+        (AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault.clone()))), vault)
+    }
+
+    async fn generate(state: AppState, request: Value) -> Result<Value, ApiError> {
+        let Json(response) = generate_keypair(State(state), Bytes::from(request.to_string())).await?;
+        Ok(serde_json::to_value(response).unwrap()["data"].clone())
+    }
+
+    #[tokio::test]
+    async fn stored_keys_cannot_also_be_returned_as_keystores() {
+        let (state, vault) = state();
+        let error = generate(state, json!({ "store": true, "password": "pw" })).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidField);
+        assert_eq!(error.field.as_deref(), Some("password"));
+        assert!(vault.list().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn stored_keys_return_only_a_handle() {
+        let (state, vault) = state();
+        let data = generate(state, json!({ "store": true, "label": "hot" })).await.unwrap();
+        assert!(data.get("secret").is_none() && data.get("keystore").is_none());
+
+        let record = vault.get(data["keyId"].as_str().unwrap()).unwrap();
+        assert_eq!(record.pubkey, data["pubkey"]);
+        assert_eq!(record.label.as_deref(), Some("hot"));
+    }
+
+    #[tokio::test]
+    async fn passwords_return_a_keystore() {
+        let (state, vault) = state();
+        let data = generate(state, json!({ "password": "pw" })).await.unwrap();
+        assert!(data.get("secret").is_none() && data.get("keyId").is_none());
+        assert!(data["keystore"].is_object());
+        assert!(vault.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use axum::{extract::{Path, State}, response::Json};
+use crate::error::{ApiErrorResponses, ApiResult};
+use crate::models::responses::{ApiResponse, KeyDeleteResponse, KeyListResponse, Versioned};
+use crate::models::v2;
+use crate::routes::ApiVersion;
+use crate::state::AppState;
+use crate::vault::{self, KeyRecord};
+
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = "keys",
+    summary = "List vault keys",
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.KeyListResponse`", body = ApiResponse<KeyListResponse>), ApiErrorResponses),
+)]
+pub async fn list_keys(
+    State(state): State<AppState>,
+    version: ApiVersion,
+) -> ApiResult<Versioned<KeyListResponse, v2::KeyListResponse>> {
+    let keys = state.vault.list()?;
+    Ok(Json(ApiResponse::success(version.convert(KeyListResponse { keys }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/keys/{key_id}",
+    tag = "keys",
+    summary = "Fetch a vault key",
+    params(("key_id" = String, Path, description = "Vault key id")),
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.KeyRecord`", body = ApiResponse<KeyRecord>), ApiErrorResponses),
+)]
+pub async fn get_key(
+    State(state): State<AppState>,
+    version: ApiVersion,
+    Path(key_id): Path<String>,
+) -> ApiResult<Versioned<KeyRecord, v2::KeyRecord>> {
+    let record = state.vault.get(&key_id)?;
+    Ok(Json(ApiResponse::success(version.convert(record))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/keys/{key_id}/disable",
+    tag = "keys",
+    summary = "Disable signing with a vault key",
+    params(("key_id" = String, Path, description = "Vault key id")),
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.KeyRecord`", body = ApiResponse<KeyRecord>), ApiErrorResponses),
+)]
+pub async fn disable_key(
+    State(state): State<AppState>,
+    version: ApiVersion,
+    Path(key_id): Path<String>,
+) -> ApiResult<Versioned<KeyRecord, v2::KeyRecord>> {
+    let record = vault::blocking(&state.vault, move |vault| vault.set_disabled(&key_id, true)).await?;
+    Ok(Json(ApiResponse::success(version.convert(record))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/keys/{key_id}/enable",
+    tag = "keys",
+    summary = "Re-enable signing with a vault key",
+    params(("key_id" = String, Path, description = "Vault key id")),
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.KeyRecord`", body = ApiResponse<KeyRecord>), ApiErrorResponses),
+)]
+pub async fn enable_key(
+    State(state): State<AppState>,
+    version: ApiVersion,
+    Path(key_id): Path<String>,
+) -> ApiResult<Versioned<KeyRecord, v2::KeyRecord>> {
+    let record = vault::blocking(&state.vault, move |vault| vault.set_disabled(&key_id, false)).await?;
+    Ok(Json(ApiResponse::success(version.convert(record))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/keys/{key_id}",
+    tag = "keys",
+    summary = "Delete a vault key",
+    params(("key_id" = String, Path, description = "Vault key id")),
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<KeyDeleteResponse>), ApiErrorResponses),
+)]
+pub async fn delete_key(
+    State(state): State<AppState>,
+    Path(key_id): Path<String>,
+) -> ApiResult<KeyDeleteResponse> {
+    let id = key_id.clone();
+    vault::blocking(&state.vault, move |vault| vault.delete(&id)).await?;
+    Ok(Json(ApiResponse::success(KeyDeleteResponse { key_id, deleted: true })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use axum::body::{self, Body};
+    use axum::extract::Request;
+    use axum::http::Method;
+    use serde_json::{json, Value};
+    use solana_sdk::signer::{keypair::Keypair, Signer};
+    use tower::Service;
+    use crate::routes;
+    use crate::signer::LocalSigner;
+    use crate::state::AppState;
+    use crate::vault::{KeyStore, MemoryKeyStore};
+
+    struct Server {
+        app: axum::Router,
+        vault: Arc<MemoryKeyStore>,
+    }
+
+    impl Server {
+        fn new() -> Self {
+            let vault = Arc::new(MemoryKeyStore::new());
+            let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault.clone())));
+            Self { app: routes::versioned_router(routes::endpoints).with_state(state), vault }
+        }
+
+        async fn send(&mut self, method: Method, path: &str, body: Option<Value>) -> Value {
+            let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+            let request = Request::builder().method(method).uri(path).body(body).unwrap();
+            let response = self.app.call(request).await.unwrap_or_else(|e| match e {});
+            let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice(&body).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_listed_disabled_enabled_and_deleted() {
+        let mut server = Server::new();
+        let keypair = Keypair::new();
+        let key_id = server.vault.insert(&keypair, Some("hot".to_string())).unwrap().key_id;
+        let path = format!("/keys/{}", key_id);
+
+        let list = server.send(Method::GET, "/keys", None).await;
+        assert_eq!(list["data"]["keys"][0]["keyId"], key_id.as_str());
+        assert_eq!(list["data"]["keys"][0]["pubkey"], keypair.pubkey().to_string());
+
+        let disabled = server.send(Method::POST, &format!("{}/disable", path), None).await;
+        assert_eq!(disabled["data"]["disabled"], true);
+        assert_eq!(server.send(Method::GET, &path, None).await["data"]["disabled"], true);
+        let enabled = server.send(Method::POST, &format!("/v2{}/enable", path), None).await;
+        assert_eq!(enabled["data"]["disabled"], false);
+
+        let deleted = server.send(Method::DELETE, &path, None).await;
+        assert_eq!(deleted["data"], json!({ "keyId": key_id, "deleted": true }));
+        assert_eq!(server.send(Method::GET, &path, None).await["code"], "KEY_NOT_FOUND");
+        assert_eq!(server.send(Method::DELETE, &path, None).await["code"], "KEY_NOT_FOUND");
+        assert_eq!(server.send(Method::GET, "/keys", None).await["data"]["keys"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn disabled_keys_are_refused_at_sign_time() {
+        let mut server = Server::new();
+        let key_id = server.vault.insert(&Keypair::new(), None).unwrap().key_id;
+        let sign = json!({ "keyId": key_id, "message": "hello" });
+
+        assert_eq!(server.send(Method::POST, "/message/sign", Some(sign.clone())).await["success"], true);
+        server.send(Method::POST, &format!("/keys/{}/disable", key_id), None).await;
+        // Refusals come back through the signer backend, which reports the vault's reason
+        let refused = server.send(Method::POST, "/message/sign", Some(sign.clone())).await;
+        assert_eq!((&refused["code"], &refused["error"]), (&json!("SIGNER_REJECTED"), &json!("Key is disabled")));
+
+        let batch = server.send(Method::POST, "/message/sign-batch", Some(json!([sign.clone()]))).await;
+        assert_eq!(batch["data"]["results"][0]["error"], "Key is disabled");
+
+        server.send(Method::POST, &format!("/keys/{}/enable", key_id), None).await;
+        assert_eq!(server.send(Method::POST, "/message/sign", Some(sign)).await["success"], true);
+    }
+}
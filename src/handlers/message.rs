@@ -1,90 +1,533 @@
-use axum::{response::Json, body::Bytes};
-use solana_sdk::signer::{keypair::Keypair, Signer};
+use axum::{extract::State, response::Json, body::Bytes};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use solana_sdk::signer::keypair::Keypair;
 use solana_sdk::signature::Signature;
 use solana_sdk::pubkey::Pubkey;
-use base64::Engine;
+use crate::error::{parse_json, parse_value, ApiError, ApiErrorResponses, ApiResult, FieldResult};
+use crate::metrics::metrics;
 use crate::models::requests::{SignMessageRequest, VerifyMessageRequest};
-use crate::models::responses::{ApiResponse, SignatureResponse, VerificationResponse};
+use crate::models::responses::{ApiResponse, BatchItem, BatchResponse, SignatureResponse, VerificationResponse, Versioned};
+use crate::models::v2;
+use crate::routes::ApiVersion;
+use crate::signer::SigningKey;
+use crate::state::AppState;
+use crate::utils::keystore::{decrypt_keypair, Keystore};
+use crate::utils::offchain::signing_payload;
+use crate::utils::redact::Redacted;
+use zeroize::Zeroizing;
 
+pub const MAX_BATCH_SIZE: usize = 10_000;
+pub const MAX_BATCH_KEYSTORES: usize = 16;
+
+#[utoipa::path(
+    post,
+    path = "/message/sign",
+    tag = "message",
+    summary = "Sign a message",
+    request_body = SignMessageRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.SignatureResponse`", body = ApiResponse<SignatureResponse>), ApiErrorResponses),
+)]
 pub async fn sign_message(
+    State(state): State<AppState>,
+    version: ApiVersion,
     body: Bytes,
-) -> Json<ApiResponse<SignatureResponse>> {
+) -> ApiResult<Versioned<SignatureResponse, v2::SignatureResponse>> {
     // Parse JSON manually
-    let request: SignMessageRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+    let request: SignMessageRequest = parse_json(&body)?;
+
+    let response = sign_one(&state, request).await?;
+    Ok(Json(ApiResponse::success(version.convert(response))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/message/verify",
+    tag = "message",
+    summary = "Verify a signed message",
+    request_body = VerifyMessageRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.VerificationResponse`", body = ApiResponse<VerificationResponse>), ApiErrorResponses),
+)]
+pub async fn verify_message(
+    version: ApiVersion,
+    body: Bytes,
+) -> ApiResult<Versioned<VerificationResponse, v2::VerificationResponse>> {
+    // Parse JSON manually
+    let request: VerifyMessageRequest = parse_json(&body)?;
+
+    let prepared = prepare_verification(request)?;
+
+    // Verify the signature
+    let is_valid = prepared.is_valid();
+    metrics().signature_verified(is_valid);
 
-    // Validate input - only check if secret is present, allow empty messages
-    if request.secret.is_empty() {
-        return Json(ApiResponse::error("Missing required fields".to_string()));
+    Ok(Json(ApiResponse::success(version.convert(prepared.into_response(is_valid)))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/message/sign-batch",
+    tag = "message",
+    summary = "Sign up to 10,000 messages",
+    request_body = Vec<SignMessageRequest>,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.SignatureResponse` per item", body = ApiResponse<BatchResponse<SignatureResponse>>), ApiErrorResponses),
+)]
+pub async fn sign_batch(
+    State(state): State<AppState>,
+    version: ApiVersion,
+    body: Bytes,
+) -> ApiResult<BatchResponse<Versioned<SignatureResponse, v2::SignatureResponse>>> {
+    let items = parse_batch(&body)?;
+
+    // Every keystore costs a scrypt derivation, so only a few may share a call
+    let keystores = items.iter().filter(|item| !item["keystore"].is_null()).count();
+    if keystores > MAX_BATCH_KEYSTORES {
+        return Err(ApiError::invalid_request(format!(
+            "At most {} batch items may use a keystore",
+            MAX_BATCH_KEYSTORES
+        )));
     }
 
-    // Decode the secret key (full keypair bytes)
-    let keypair_bytes = match bs58::decode(&request.secret).into_vec() {
-        Ok(bytes) => bytes,
-        Err(_) => return Json(ApiResponse::error("Invalid secret key format".to_string())),
-    };
+    let mut results = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let result = match parse_value::<SignMessageRequest>(item) {
+            Ok(request) => sign_one(&state, request).await,
+            Err(e) => Err(e),
+        };
+        results.push(BatchItem::new(index, result.map(|response| version.convert(response))));
+    }
 
-    // Create keypair from bytes
-    let keypair = match Keypair::from_bytes(&keypair_bytes) {
-        Ok(kp) => kp,
-        Err(_) => return Json(ApiResponse::error("Invalid secret key".to_string())),
-    };
+    Ok(Json(ApiResponse::success(BatchResponse { results })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/message/verify-batch",
+    tag = "message",
+    summary = "Verify up to 10,000 signatures",
+    request_body = Vec<VerifyMessageRequest>,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is a `v2.VerificationResponse` per item", body = ApiResponse<BatchResponse<VerificationResponse>>), ApiErrorResponses),
+)]
+pub async fn verify_batch(
+    version: ApiVersion,
+    body: Bytes,
+) -> ApiResult<BatchResponse<Versioned<VerificationResponse, v2::VerificationResponse>>> {
+    let items = parse_batch(&body)?;
+
+    let prepared: Vec<Result<PreparedVerification, ApiError>> = items
+        .into_iter()
+        .map(|item| parse_value::<VerifyMessageRequest>(item).and_then(prepare_verification))
+        .collect();
+
+    // Batch verification only answers "all valid or not", so it is the fast path; when it
+    // fails, each item is checked on its own to find the invalid ones
+    let verdicts = tokio::task::spawn_blocking(move || {
+        let verdicts = batch_verdicts(&prepared);
+        (prepared, verdicts)
+    })
+    .await;
+
+    let (prepared, verdicts) = verdicts.map_err(|_| ApiError::internal("Batch verification failed"))?;
+
+    let results = prepared
+        .into_iter()
+        .zip(verdicts)
+        .enumerate()
+        .map(|(index, (prepared, valid))| {
+            if prepared.is_ok() {
+                metrics().signature_verified(valid);
+            }
+            BatchItem::new(index, prepared.map(|p| version.convert(p.into_response(valid))))
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(BatchResponse { results })))
+}
 
-    // Sign the message
-    let message_bytes = request.message.as_bytes();
-    let signature = keypair.sign_message(message_bytes);
+fn parse_batch(body: &[u8]) -> Result<Vec<serde_json::Value>, ApiError> {
+    // Items are decoded one by one so a malformed entry only fails its own index
+    let items: Vec<serde_json::Value> = parse_json(body).map_err(|mut e| {
+        e.message = "Request body must be a JSON array".to_string();
+        e
+    })?;
 
-    let response = SignatureResponse {
-        signature: base64::engine::general_purpose::STANDARD.encode(signature.as_ref()),
-        public_key: keypair.pubkey().to_string(),
+    if items.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::invalid_request(format!("Batch size must not exceed {}", MAX_BATCH_SIZE)));
+    }
+
+    Ok(items)
+}
+
+async fn sign_one(state: &AppState, request: SignMessageRequest) -> Result<SignatureResponse, ApiError> {
+    // Resolve the signing key from a key id, a raw secret or an encrypted keystore
+    let key = signing_key(request.key_id, request.secret, request.keystore, request.password).await?;
+
+    let message_bytes = request.encoding.decode(&request.message).field("message")?;
+
+    // Build the signed payload (raw bytes or the off-chain message envelope)
+    let (payload, message_format) = signing_payload(request.format, &message_bytes).field("message")?;
+
+    // Sign the message through the configured signer backend
+    let signed = state.signer.sign(&key, &payload).await?;
+    metrics().signature_produced("ed25519");
+
+    Ok(SignatureResponse {
+        signature: request.signature_encoding.encode(signed.signature.as_ref()),
+        public_key: signed.pubkey.to_string(),
         message: request.message,
-    };
+        message_format: message_format.map(str::to_string),
+    })
+}
 
-    Json(ApiResponse::success(response))
+pub(crate) struct PreparedVerification {
+    pubkey: Pubkey,
+    signature: Signature,
+    payload: Vec<u8>,
+    message: String,
+    pubkey_str: String,
+    message_format: Option<&'static str>,
 }
 
-pub async fn verify_message(
-    body: Bytes,
-) -> Json<ApiResponse<VerificationResponse>> {
-    // Parse JSON manually
-    let request: VerifyMessageRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+impl PreparedVerification {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.signature.verify(&self.pubkey.to_bytes(), &self.payload)
+    }
 
+    fn into_response(self, valid: bool) -> VerificationResponse {
+        VerificationResponse {
+            valid,
+            message: self.message,
+            pubkey: self.pubkey_str,
+            message_format: self.message_format.map(str::to_string),
+        }
+    }
+}
+
+pub(crate) fn prepare_verification(request: VerifyMessageRequest) -> Result<PreparedVerification, ApiError> {
     // Validate input - allow empty messages, but require signature and pubkey
-    if request.signature.is_empty() || request.pubkey.is_empty() {
-        return Json(ApiResponse::error("Missing required fields".to_string()));
+    if request.signature.is_empty() {
+        return Err(ApiError::missing_field("signature"));
+    }
+    if request.pubkey.is_empty() {
+        return Err(ApiError::missing_field("pubkey"));
     }
 
     // Parse public key
-    let pubkey: Pubkey = match request.pubkey.parse() {
-        Ok(pk) => pk,
-        Err(_) => return Json(ApiResponse::error("Invalid public key format".to_string())),
-    };
+    let pubkey: Pubkey = request
+        .pubkey
+        .parse()
+        .map_err(|_| ApiError::invalid_field("pubkey", "Invalid public key format"))?;
 
     // Decode signature
-    let signature_bytes = match base64::engine::general_purpose::STANDARD.decode(&request.signature) {
-        Ok(bytes) => bytes,
-        Err(_) => return Json(ApiResponse::error("Invalid signature format".to_string())),
-    };
+    let signature_bytes = request.signature_encoding.decode(&request.signature).field("signature")?;
+    let signature = Signature::try_from(signature_bytes.as_slice())
+        .map_err(|_| ApiError::invalid_field("signature", "Invalid signature"))?;
+
+    let message_bytes = request.encoding.decode(&request.message).field("message")?;
+    let (payload, message_format) = signing_payload(request.format, &message_bytes).field("message")?;
+
+    Ok(PreparedVerification {
+        pubkey,
+        signature,
+        payload,
+        message: request.message,
+        pubkey_str: request.pubkey,
+        message_format,
+    })
+}
 
-    let signature = match Signature::try_from(signature_bytes.as_slice()) {
-        Ok(sig) => sig,
-        Err(_) => return Json(ApiResponse::error("Invalid signature".to_string())),
+fn batch_verdicts(prepared: &[Result<PreparedVerification, ApiError>]) -> Vec<bool> {
+    verdicts_with(prepared, PreparedVerification::is_valid)
+}
+
+// `check` is the strict single verification, used for everything the batch cannot decide
+fn verdicts_with(
+    prepared: &[Result<PreparedVerification, ApiError>],
+    check: impl Fn(&PreparedVerification) -> bool,
+) -> Vec<bool> {
+    let mut verdicts = vec![false; prepared.len()];
+    let mut batch = Vec::new();
+    for (index, item) in prepared.iter().enumerate() {
+        let Ok(item) = item else {
+            continue;
+        };
+        match screen(item) {
+            Screened::Rejected => {}
+            Screened::Single => verdicts[index] = check(item),
+            Screened::Batch(pubkey, signature) => batch.push((index, item, pubkey, signature)),
+        }
+    }
+    if batch.is_empty() {
+        return verdicts;
+    }
+
+    let messages: Vec<&[u8]> = batch.iter().map(|(_, item, _, _)| item.payload.as_slice()).collect();
+    let signatures: Vec<_> = batch.iter().map(|(_, _, _, signature)| *signature).collect();
+    let public_keys: Vec<_> = batch.iter().map(|(_, _, pubkey, _)| **pubkey).collect();
+    let all_valid = ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).is_ok();
+    for (index, item, _, _) in batch {
+        verdicts[index] = all_valid || check(item);
+    }
+    verdicts
+}
+
+enum Screened {
+    /// Fails the strict check whatever the message.
+    Rejected,
+    /// Needs the strict check on its own.
+    Single,
+    Batch(Box<ed25519_dalek::PublicKey>, ed25519_dalek::Signature),
+}
+
+// Batch verification scales each `sB = R + kA` by a random factor before summing, so a
+// small-order error term can vanish: always with a small-order key, one time in eight
+// with a torsioned one. `/message/verify` demands equality and rejects small-order keys
+// and R. For keys and R in the prime-order subgroup the two agree, so only those go into
+// the batch. Small-order values are rejected as the strict check would; other torsioned
+// or non-canonically encoded ones are left to the strict check.
+fn screen(item: &PreparedVerification) -> Screened {
+    let signature_bytes: &[u8] = item.signature.as_ref();
+    let pubkey = CompressedEdwardsY(item.pubkey.to_bytes());
+    let r = CompressedEdwardsY::from_slice(&signature_bytes[..32]);
+    let signature = ed25519_dalek::Signature::from_bytes(signature_bytes);
+    let (Some(pubkey_point), Some(r_point), Ok(signature)) = (pubkey.decompress(), r.decompress(), signature) else {
+        return Screened::Rejected;
     };
 
-    // Verify the signature
-    let message_bytes = request.message.as_bytes();
-    let is_valid = signature.verify(&pubkey.to_bytes(), message_bytes);
+    if pubkey_point.is_small_order() || r_point.is_small_order() {
+        return Screened::Rejected;
+    }
+    let canonical = pubkey_point.compress() == pubkey && r_point.compress() == r;
+    if !canonical || !pubkey_point.is_torsion_free() || !r_point.is_torsion_free() {
+        return Screened::Single;
+    }
+    match ed25519_dalek::PublicKey::from_bytes(pubkey.as_bytes()) {
+        Ok(pubkey) => Screened::Batch(Box::new(pubkey), signature),
+        Err(_) => Screened::Rejected,
+    }
+}
 
-    let response = VerificationResponse {
-        valid: is_valid,
-        message: request.message,
-        pubkey: request.pubkey,
+pub(crate) async fn signing_key(
+    key_id: Option<String>,
+    secret: Redacted<String>,
+    keystore: Option<Keystore>,
+    password: Option<Redacted<String>>,
+) -> Result<SigningKey, ApiError> {
+    if let Some(key_id) = key_id {
+        return Ok(SigningKey::Handle(key_id));
+    }
+
+    if !secret.is_empty() {
+        // Decode the secret key (full keypair bytes); the Keypair wipes its own copy on drop
+        let keypair_bytes = bs58::decode(secret.expose())
+            .into_vec()
+            .map(Zeroizing::new)
+            .map_err(|_| ApiError::invalid_field("secret", "Invalid secret key format"))?;
+
+        return Keypair::from_bytes(&keypair_bytes)
+            .map(SigningKey::Inline)
+            .map_err(|_| ApiError::invalid_field("secret", "Invalid secret key"));
+    }
+
+    let (keystore, password) = match (keystore, password) {
+        (Some(keystore), Some(password)) => (keystore, password),
+        (Some(_), None) => return Err(ApiError::missing_field("password")),
+        _ => return Err(ApiError::missing_field("secret")),
     };
 
-    Json(ApiResponse::success(response))
-} 
+    tokio::task::spawn_blocking(move || decrypt_keypair(&keystore, &password))
+        .await
+        .map_err(|_| ApiError::internal("Failed to decrypt keystore"))?
+        .map(SigningKey::Inline)
+        .field("keystore")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION};
+    use curve25519_dalek::scalar::Scalar;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha512};
+    use solana_sdk::signer::Signer;
+    use std::sync::Arc;
+    use crate::signer::LocalSigner;
+    use crate::utils::encoding::SignatureEncoding;
+    use crate::vault::MemoryKeyStore;
+
+    async fn single(request: &Value) -> bool {
+        let body = Bytes::from(request.to_string());
+        let Json(response) = verify_message(ApiVersion::V1, body).await.unwrap();
+        serde_json::to_value(response).unwrap()["data"]["valid"].as_bool().unwrap()
+    }
+
+    async fn batch(requests: &[Value]) -> Vec<bool> {
+        let body = Bytes::from(Value::from(requests.to_vec()).to_string());
+        let Json(response) = verify_batch(ApiVersion::V1, body).await.unwrap();
+        serde_json::to_value(response).unwrap()["data"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["data"]["valid"].as_bool().unwrap())
+            .collect()
+    }
+
+    fn signed(keypair: &Keypair, message: &str) -> Value {
+        json!({
+            "message": message,
+            "signature": SignatureEncoding::Base64.encode(keypair.sign_message(message.as_bytes()).as_ref()),
+            "pubkey": keypair.pubkey().to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn mismatched_signature_is_rejected_by_both_endpoints() {
+        let keypair = Keypair::new();
+        let valid = signed(&keypair, "hello");
+        let mut mismatched = signed(&keypair, "hello");
+        mismatched["message"] = json!("goodbye");
+
+        assert!(single(&valid).await);
+        assert!(!single(&mismatched).await);
+        assert_eq!(batch(&[valid.clone(), mismatched.clone(), valid]).await, [true, false, true]);
+    }
+
+    #[tokio::test]
+    async fn small_order_public_key_is_rejected_by_both_endpoints() {
+        // With the identity as public key, R = rB and s = r satisfy the unstrict ed25519
+        // equation for any message, which a plain batch check would accept
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let r = Scalar::from(7u64);
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice((r * ED25519_BASEPOINT_POINT).compress().as_bytes());
+        signature[32..].copy_from_slice(r.as_bytes());
+        let forged = json!({
+            "message": "hello",
+            "signature": SignatureEncoding::Base64.encode(&signature),
+            "pubkey": Pubkey::new_from_array(identity).to_string(),
+        });
+
+        assert!(!single(&forged).await);
+        assert_eq!(batch(&[signed(&Keypair::new(), "hello"), forged]).await, [true, false]);
+    }
+
+    fn prepared(requests: &[Value]) -> Vec<Result<PreparedVerification, ApiError>> {
+        requests
+            .iter()
+            .map(|request| parse_value::<VerifyMessageRequest>(request.clone()).and_then(prepare_verification))
+            .collect()
+    }
+
+    #[test]
+    fn valid_batches_skip_the_single_checks() {
+        let items: Vec<Value> = (0..8).map(|i| signed(&Keypair::new(), &format!("message {}", i))).collect();
+        let verdicts = verdicts_with(&prepared(&items), |_| panic!("checked one by one"));
+        assert_eq!(verdicts, [true; 8]);
+
+        // One bad item sends the rest of the batch through the single check
+        let mut items = items;
+        items[3]["message"] = json!("tampered");
+        let checked = std::cell::Cell::new(0);
+        let verdicts = verdicts_with(&prepared(&items), |item| {
+            checked.set(checked.get() + 1);
+            item.is_valid()
+        });
+        assert_eq!(checked.get(), 8);
+        assert_eq!(verdicts.iter().filter(|valid| !**valid).count(), 1);
+        assert!(!verdicts[3]);
+    }
+
+    #[tokio::test]
+    async fn mixed_order_public_key_gets_the_strict_verdict() {
+        // A = aB + T with T of order 8 is not small-order, so the strict check accepts the
+        // key, but the signature is only valid up to the small-order term kT
+        let a = Scalar::from(11u64);
+        let r = Scalar::from(13u64);
+        let public_key = (a * ED25519_BASEPOINT_POINT + EIGHT_TORSION[1]).compress();
+        let r_point = (r * ED25519_BASEPOINT_POINT).compress();
+
+        let (message, k) = (0..)
+            .map(|i| {
+                let message = format!("hello {}", i);
+                let hash = Sha512::new()
+                    .chain_update(r_point.as_bytes())
+                    .chain_update(public_key.as_bytes())
+                    .chain_update(message.as_bytes())
+                    .finalize();
+                (message, Scalar::from_bytes_mod_order_wide(&hash.into()))
+            })
+            .find(|(_, k)| k.as_bytes()[0] % 8 != 0)
+            .unwrap();
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(r_point.as_bytes());
+        signature[32..].copy_from_slice((r + k * a).as_bytes());
+        let torsioned = json!({
+            "message": message,
+            "signature": SignatureEncoding::Base64.encode(&signature),
+            "pubkey": Pubkey::new_from_array(public_key.to_bytes()).to_string(),
+        });
+
+        assert!(!single(&torsioned).await);
+        assert_eq!(batch(&[signed(&Keypair::new(), "hello"), torsioned.clone()]).await, [true, false]);
+
+        // The batch would let it through for one random factor in eight
+        let items = prepared(&[signed(&Keypair::new(), "hello"), torsioned]);
+        for _ in 0..64 {
+            assert_eq!(batch_verdicts(&items), [true, false]);
+        }
+    }
+
+    #[tokio::test]
+    async fn signatures_verify_across_message_and_signature_encodings() {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        let keypair = Keypair::new();
+        let bytes = b"hello\x00\xff";
+
+        for (encoding, message) in [
+            ("hex", hex::encode(bytes)),
+            ("base64", SignatureEncoding::Base64.encode(bytes)),
+            ("base58", bs58::encode(bytes).into_string()),
+        ] {
+            for signature_encoding in ["base58", "hex"] {
+                let request = json!({
+                    "message": message,
+                    "secret": keypair.to_base58_string(),
+                    "encoding": encoding,
+                    "signatureEncoding": signature_encoding,
+                });
+                let Json(response) = sign_message(State(state.clone()), ApiVersion::V1, Bytes::from(request.to_string()))
+                    .await
+                    .unwrap();
+                let data = serde_json::to_value(response).unwrap()["data"].take();
+                assert_eq!(data["public_key"], json!(keypair.pubkey().to_string()));
+
+                // The signature covers the decoded bytes, not the encoded text
+                let expected = keypair.sign_message(bytes);
+                let signature = data["signature"].as_str().unwrap();
+                let decoded = if signature_encoding == "hex" {
+                    hex::decode(signature).unwrap()
+                } else {
+                    bs58::decode(signature).into_vec().unwrap()
+                };
+                assert_eq!(decoded, expected.as_ref(), "{} {}", encoding, signature_encoding);
+
+                let verify = json!({
+                    "message": message,
+                    "signature": signature,
+                    "pubkey": keypair.pubkey().to_string(),
+                    "encoding": encoding,
+                    "signatureEncoding": signature_encoding,
+                });
+                assert!(single(&verify).await, "{} {}", encoding, signature_encoding);
+                assert_eq!(batch(&[verify.clone(), signed(&keypair, "hello")]).await, [true, true]);
+
+                // The same signature read in the default encodings does not verify
+                let mut misread = verify.clone();
+                misread["encoding"] = json!("utf8");
+                assert!(!single(&misread).await, "{} {}", encoding, signature_encoding);
+            }
+        }
+    }
+}
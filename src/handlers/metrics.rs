@@ -0,0 +1,86 @@
+use axum::{http::header, response::IntoResponse};
+use crate::metrics::metrics;
+
+pub async fn render_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use axum::body::{self, Body};
+    use axum::extract::Request;
+    use axum::http::Method;
+    use serde_json::{json, Value};
+    use solana_sdk::signer::{keypair::Keypair, Signer};
+    use tower::Service;
+    use crate::config::Config;
+    use crate::routes::{self, RouteTable};
+    use crate::signer::LocalSigner;
+    use crate::state::AppState;
+    use crate::vault::MemoryKeyStore;
+
+    async fn send(app: &mut axum::Router, method: Method, path: &str, body: Option<Value>) -> (Value, String) {
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+        let request = Request::builder().method(method).uri(path).body(body).unwrap();
+        let response = app.call(request).await.unwrap_or_else(|e| match e {});
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (serde_json::from_slice(&body).unwrap_or(Value::Null), String::from_utf8_lossy(&body).into_owned())
+    }
+
+    // The value of `solana_server_<name>` in the sample carrying every label in `labels`
+    fn sample(scrape: &str, name: &str, labels: &[(&str, &str)]) -> f64 {
+        let prefix = format!("solana_server_{}{{", name);
+        scrape
+            .lines()
+            .filter_map(|line| line.strip_prefix(&prefix))
+            .filter_map(|line| line.split_once("} "))
+            .find(|(set, _)| labels.iter().all(|(key, value)| set.contains(&format!("{}=\"{}\"", key, value))))
+            .map_or(0.0, |(_, value)| value.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn scrapes_count_requests_errors_and_domain_events() {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+            .with_routes(RouteTable::new(&routes::endpoints()));
+        let mut app = routes::app(&Config::default(), state).unwrap();
+        let (_, before) = send(&mut app, Method::GET, "/metrics", None).await;
+
+        let keypair = Keypair::new();
+        let (generated, _) = send(&mut app, Method::POST, "/keypair", None).await;
+        assert_eq!(generated["success"], true);
+        send(&mut app, Method::GET, "/keys/no-such-key-id", None).await;
+        send(&mut app, Method::GET, "/v2/keys/no-such-key-id", None).await;
+        send(&mut app, Method::GET, "/no/such/path", None).await;
+        let sign = json!({ "message": "hello", "secret": keypair.to_base58_string() });
+        let (signed, _) = send(&mut app, Method::POST, "/message/sign", Some(sign)).await;
+        let verify = json!({ "message": "hello", "signature": signed["data"]["signature"], "pubkey": keypair.pubkey().to_string() });
+        send(&mut app, Method::POST, "/message/verify", Some(verify)).await;
+        let transfer = json!({ "from": keypair.pubkey().to_string(), "to": Keypair::new().pubkey().to_string(), "lamports": 5 });
+        send(&mut app, Method::POST, "/v2/send/sol", Some(transfer)).await;
+
+        let (_, after) = send(&mut app, Method::GET, "/metrics", None).await;
+        let grew = |name: &str, labels: &[(&str, &str)]| sample(&after, name, labels) - sample(&before, name, labels);
+
+        // Routes are labelled by template, never by the key id in the path
+        assert!(!after.contains("no-such-key-id"));
+        let key_lookup = [("route", "/keys/:key_id"), ("method", "GET"), ("status", "404")];
+        assert!(grew("http_requests_total", &key_lookup) >= 1.0);
+        assert!(grew("http_requests_total", &[("route", "/v2/keys/:key_id"), ("status", "404")]) >= 1.0);
+        assert!(grew("http_requests_total", &[("route", "unmatched"), ("status", "404")]) >= 1.0);
+        assert!(grew("http_requests_total", &[("route", "/keypair"), ("method", "POST"), ("status", "200")]) >= 1.0);
+        assert!(sample(&after, "http_request_duration_seconds_count", &[("route", "/keypair"), ("method", "POST")]) >= 1.0);
+
+        assert!(grew("http_errors_total", &[("route", "/keys/:key_id"), ("code", "KEY_NOT_FOUND")]) >= 1.0);
+        assert!(grew("http_errors_total", &[("route", "unmatched"), ("code", "NOT_FOUND")]) >= 1.0);
+
+        assert!(grew("keypairs_generated_total", &[("storage", "plain")]) >= 1.0);
+        assert!(grew("signatures_produced_total", &[("scheme", "ed25519")]) >= 1.0);
+        assert!(grew("signatures_verified_total", &[("result", "valid")]) >= 1.0);
+        assert!(grew("instructions_built_total", &[("program", "system")]) >= 1.0);
+    }
+}
@@ -1,4 +1,12 @@
+pub mod auth;
+pub mod docs;
+pub mod health;
 pub mod keypair;
+pub mod keys;
 pub mod message;
+pub mod metrics;
+pub mod precompile;
+pub mod rent;
+pub mod rpc;
 pub mod token;
-pub mod transfer; 
+pub mod transfer; 
\ No newline at end of file
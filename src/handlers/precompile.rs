@@ -0,0 +1,194 @@
+use axum::{extract::State, response::Json, body::Bytes};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use crate::error::{parse_json, ApiError, ApiErrorResponses, ApiResult, FieldResult};
+use crate::handlers::message::signing_key;
+use crate::metrics::metrics;
+use crate::models::requests::{
+    Ed25519InstructionRequest, Ed25519SignatureEntry, Secp256k1InstructionRequest, Secp256k1SignatureEntry,
+};
+use crate::models::instruction::ApiInstruction;
+use crate::models::responses::{ApiResponse, InstructionResponse, Versioned};
+use crate::routes::ApiVersion;
+use crate::state::AppState;
+use crate::utils::precompile::{
+    ed25519_instruction, eth_address, secp256k1_instruction, secp256k1_message_hash, Ed25519Entry, Secp256k1Entry,
+};
+use zeroize::Zeroizing;
+
+#[utoipa::path(
+    post,
+    path = "/precompile/ed25519",
+    tag = "precompile",
+    summary = "Build an Ed25519 program instruction",
+    request_body = Ed25519InstructionRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<InstructionResponse>), ApiErrorResponses),
+)]
+pub async fn build_ed25519_instruction(
+    State(state): State<AppState>,
+    version: ApiVersion,
+    body: Bytes,
+) -> ApiResult<Versioned<InstructionResponse, ApiInstruction>> {
+    // Parse JSON manually
+    let request: Ed25519InstructionRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
+
+    let mut entries = Vec::with_capacity(request.signatures.len());
+    for (index, entry) in request.signatures.into_iter().enumerate() {
+        let entry = ed25519_entry(&state, entry).await.map_err(|e| entry_error(index, e))?;
+        entries.push(entry);
+    }
+
+    let instruction = ed25519_instruction(&entries).field("signatures")?;
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, InstructionResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/precompile/secp256k1",
+    tag = "precompile",
+    summary = "Build a Secp256k1 program instruction",
+    request_body = Secp256k1InstructionRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<InstructionResponse>), ApiErrorResponses),
+)]
+pub async fn build_secp256k1_instruction(
+    version: ApiVersion,
+    body: Bytes,
+) -> ApiResult<Versioned<InstructionResponse, ApiInstruction>> {
+    // Parse JSON manually
+    let request: Secp256k1InstructionRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
+
+    let mut entries = Vec::with_capacity(request.signatures.len());
+    for (index, entry) in request.signatures.into_iter().enumerate() {
+        let entry = secp256k1_entry(entry).map_err(|e| entry_error(index, e))?;
+        entries.push(entry);
+    }
+
+    let instruction = secp256k1_instruction(&entries, request.instruction_index).field("signatures")?;
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, InstructionResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
+}
+
+// Scopes an entry error to its position, e.g. `signatures[2].pubkey`
+fn entry_error(index: usize, mut error: ApiError) -> ApiError {
+    error.message = format!("Signature {}: {}", index, error.message);
+    error.field = Some(match error.field {
+        Some(field) => format!("signatures[{}].{}", index, field),
+        None => format!("signatures[{}]", index),
+    });
+    error
+}
+
+async fn ed25519_entry(state: &AppState, entry: Ed25519SignatureEntry) -> Result<Ed25519Entry, ApiError> {
+    let message = entry.encoding.decode(&entry.message).field("message")?;
+
+    // Sign on the caller's behalf when key material or a key id is supplied
+    if entry.key_id.is_some() || !entry.secret.is_empty() {
+        let key = signing_key(entry.key_id, entry.secret, None, None).await?;
+        let signed = state.signer.sign(&key, &message).await?;
+        metrics().signature_produced("ed25519");
+        return Ok(Ed25519Entry {
+            pubkey: signed.pubkey.to_bytes(),
+            signature: signed.signature.into(),
+            message,
+        });
+    }
+
+    if entry.pubkey.is_empty() {
+        return Err(ApiError::missing_field("pubkey"));
+    }
+    if entry.signature.is_empty() {
+        return Err(ApiError::missing_field("signature"));
+    }
+
+    let pubkey: Pubkey = entry
+        .pubkey
+        .parse()
+        .map_err(|_| ApiError::invalid_field("pubkey", "Invalid public key format"))?;
+    let signature_bytes = entry.signature_encoding.decode(&entry.signature).field("signature")?;
+    let signature = Signature::try_from(signature_bytes.as_slice())
+        .map_err(|_| ApiError::invalid_field("signature", "Invalid signature"))?;
+
+    // The precompile would fail the whole transaction; catch it before it is built
+    if !signature.verify(pubkey.as_ref(), &message) {
+        return Err(ApiError::invalid_field("signature", "Signature does not verify"));
+    }
+
+    Ok(Ed25519Entry {
+        pubkey: pubkey.to_bytes(),
+        signature: signature.into(),
+        message,
+    })
+}
+
+fn secp256k1_entry(entry: Secp256k1SignatureEntry) -> Result<Secp256k1Entry, ApiError> {
+    let message = entry.encoding.decode(&entry.message).field("message")?;
+    let hash = secp256k1_message_hash(&message);
+
+    if !entry.secret.is_empty() {
+        let secret_bytes = decode_hex(entry.secret.expose())
+            .map(Zeroizing::new)
+            .ok_or_else(|| ApiError::invalid_field("secret", "Invalid secret key format"))?;
+        // libsecp256k1's SecretKey is `Copy` and cannot be wiped; only our buffers are
+        let secret = libsecp256k1::SecretKey::parse_slice(&secret_bytes)
+            .map_err(|_| ApiError::invalid_field("secret", "Invalid secret key"))?;
+        let (signature, recovery_id) = libsecp256k1::sign(&hash, &secret);
+        metrics().signature_produced("secp256k1");
+
+        return Ok(Secp256k1Entry {
+            eth_address: eth_address(&libsecp256k1::PublicKey::from_secret_key(&secret)),
+            signature: signature.serialize(),
+            recovery_id: recovery_id.serialize(),
+            message,
+        });
+    }
+
+    if entry.eth_address.is_empty() {
+        return Err(ApiError::missing_field("ethAddress"));
+    }
+    if entry.signature.is_empty() {
+        return Err(ApiError::missing_field("signature"));
+    }
+
+    let address: [u8; 20] = decode_hex(&entry.eth_address)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::invalid_field("ethAddress", "Invalid Ethereum address"))?;
+    let signature_bytes: [u8; 64] = entry
+        .signature_encoding
+        .decode(&entry.signature)
+        .field("signature")?
+        .try_into()
+        .map_err(|_| ApiError::invalid_field("signature", "Invalid signature"))?;
+
+    // Accept Ethereum-style `v` (27/28) as well as the raw recovery id
+    let recovery_id = match entry.recovery_id {
+        Some(v) if v >= 27 => v - 27,
+        Some(v) => v,
+        None => return Err(ApiError::missing_field("recoveryId")),
+    };
+    let parsed_recovery_id = libsecp256k1::RecoveryId::parse(recovery_id)
+        .map_err(|_| ApiError::invalid_field("recoveryId", "Invalid recovery id"))?;
+    let signature = libsecp256k1::Signature::parse_standard(&signature_bytes)
+        .map_err(|_| ApiError::invalid_field("signature", "Invalid signature"))?;
+
+    let recovered = libsecp256k1::recover(&hash, &signature, &parsed_recovery_id)
+        .map_err(|_| ApiError::invalid_field("signature", "Signature does not verify"))?;
+    if eth_address(&recovered) != address {
+        return Err(ApiError::invalid_field("signature", "Signature does not verify"));
+    }
+
+    Ok(Secp256k1Entry {
+        eth_address: address,
+        signature: signature_bytes,
+        recovery_id,
+        message,
+    })
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()
+}
@@ -0,0 +1,29 @@
+use axum::{extract::{Path, State}, response::Json};
+use crate::error::{ApiError, ApiErrorResponses, ApiResult};
+use crate::models::responses::{ApiResponse, RentResponse};
+use crate::state::AppState;
+
+// Largest account the runtime allows (MAX_PERMITTED_DATA_LENGTH)
+const MAX_ACCOUNT_SPACE: u64 = 10 * 1024 * 1024;
+
+#[utoipa::path(
+    get,
+    path = "/rent/{space}",
+    tag = "rent",
+    summary = "Rent-exempt minimum balance for an account size",
+    params(("space" = u64, Path, description = "Account data length in bytes, e.g. 82 for a mint or 165 for a token account")),
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`", body = ApiResponse<RentResponse>), ApiErrorResponses),
+)]
+pub async fn rent_exempt_minimum(
+    State(state): State<AppState>,
+    Path(space): Path<String>,
+) -> ApiResult<RentResponse> {
+    let space: u64 = space
+        .parse()
+        .ok()
+        .filter(|space| *space <= MAX_ACCOUNT_SPACE)
+        .ok_or_else(|| ApiError::invalid_field("space", "Invalid account size"))?;
+
+    let lamports = state.rent.minimum_balance(space as usize);
+    Ok(Json(ApiResponse::success(RentResponse { space, lamports })))
+}
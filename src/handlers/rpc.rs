@@ -0,0 +1,421 @@
+use std::net::SocketAddr;
+use axum::{
+    body::{self, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Extension, Router,
+};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use tower::Service;
+use crate::error::ErrorCode;
+use crate::middleware::request_id::{RequestId, REQUEST_ID_HEADER};
+use crate::models::responses::ApiResponse;
+use crate::models::rpc::{
+    RpcError, RpcErrorData, RpcRequest, RpcResponse, INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST,
+    METHOD_NOT_FOUND, PARSE_ERROR, SERVER_ERROR,
+};
+use crate::routes::{ApiVersion, RouteTable};
+use crate::state::AppState;
+use crate::tls::ClientCertificate;
+
+pub const MAX_RPC_BATCH: usize = 100;
+
+/// The complete application, attached to requests so `/rpc` can run each call through
+/// the same middleware and handlers as the REST request it stands for. The attached
+/// router does not carry the extension itself, so calls cannot recurse into `/rpc`.
+#[derive(Clone)]
+pub struct Dispatcher {
+    app: Router,
+}
+
+impl Dispatcher {
+    pub fn new(app: Router) -> Self {
+        Self { app }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/rpc",
+    tag = "rpc",
+    summary = "Call operations as JSON-RPC 2.0 methods",
+    description = "Accepts a single call or a batch array. Every REST operation is a method \
+        (`generateKeypair`, `signMessage`, `createToken`, `sendSol`, ...), and each call is \
+        authorized, rate limited and answered exactly like the REST request it stands for; \
+        `/v2/rpc` returns the v2 shapes. Calls without an `id` are notifications.",
+    request_body = RpcRequest,
+    responses(
+        (status = 200, description = "One response per call that has an id, in an array for batches", body = RpcResponse),
+        (status = 204, description = "Only notifications were sent"),
+    ),
+)]
+pub async fn handle_rpc(
+    version: ApiVersion,
+    dispatcher: Option<Extension<Dispatcher>>,
+    State(state): State<AppState>,
+    request: Request,
+) -> Response {
+    let Some(Extension(dispatcher)) = dispatcher else {
+        return reply(Some(RpcResponse::error(Value::Null, RpcError::new(INTERNAL_ERROR, "RPC dispatch unavailable"))));
+    };
+    let (parts, body) = request.into_parts();
+    let context = CallContext {
+        dispatcher,
+        version,
+        headers: forwarded_headers(&parts.headers),
+        remote: parts.extensions.get::<ConnectInfo<SocketAddr>>().cloned(),
+        client_cert: parts.extensions.get::<ClientCertificate>().cloned(),
+        request_id: parts.extensions.get::<RequestId>().map(|id| id.0.clone()),
+    };
+
+    // `limit_body` has already capped the size
+    let payload = match body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => serde_json::from_slice::<Value>(&bytes).ok(),
+        Err(_) => None,
+    };
+    let Some(payload) = payload else {
+        return reply(Some(RpcResponse::error(Value::Null, RpcError::new(PARSE_ERROR, "Parse error"))));
+    };
+
+    match payload {
+        Value::Array(calls) if calls.is_empty() => {
+            reply(Some(RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, "Empty batch"))))
+        }
+        Value::Array(calls) if calls.len() > MAX_RPC_BATCH => reply(Some(RpcResponse::error(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, format!("Batch exceeds {} calls", MAX_RPC_BATCH)),
+        ))),
+        Value::Array(calls) => {
+            // Calls run in order, so a batch behaves like the same REST requests sent one by one
+            let mut responses = Vec::with_capacity(calls.len());
+            for (index, call) in calls.into_iter().enumerate() {
+                responses.extend(context.run(&state.routes, call, Some(index)).await);
+            }
+            if responses.is_empty() {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                Json(responses).into_response()
+            }
+        }
+        call => reply(context.run(&state.routes, call, None).await),
+    }
+}
+
+fn reply(response: Option<RpcResponse>) -> Response {
+    match response {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+struct CallContext {
+    dispatcher: Dispatcher,
+    version: ApiVersion,
+    headers: HeaderMap,
+    remote: Option<ConnectInfo<SocketAddr>>,
+    client_cert: Option<ClientCertificate>,
+    request_id: Option<String>,
+}
+
+impl CallContext {
+    /// Runs one call; `None` for notifications.
+    async fn run(&self, routes: &RouteTable, call: Value, index: Option<usize>) -> Option<RpcResponse> {
+        let call = match parse_call(call) {
+            Ok(call) => call,
+            Err(response) => return Some(*response),
+        };
+
+        let outcome = match routes.rpc_method(&call.method) {
+            Some((method, path)) => match build_request(method, path, call.params) {
+                Ok((uri, body)) => self.dispatch(method, &uri, body, index).await,
+                Err(message) => Err(RpcError::new(INVALID_PARAMS, message)),
+            },
+            None => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", call.method))),
+        };
+
+        let id = call.id?;
+        Some(match outcome {
+            Ok(result) => RpcResponse::result(id, result),
+            Err(error) => RpcResponse::error(id, error),
+        })
+    }
+
+    async fn dispatch(
+        &self,
+        method: &Method,
+        uri: &str,
+        body: Vec<u8>,
+        index: Option<usize>,
+    ) -> Result<Box<RawValue>, RpcError> {
+        let uri = format!("{}{}", self.version.prefix(), uri);
+        let mut request = Request::new(Body::from(body));
+        *request.method_mut() = method.clone();
+        *request.uri_mut() = uri.parse().map_err(|_| RpcError::new(INVALID_PARAMS, "Invalid path parameter"))?;
+        *request.headers_mut() = self.headers.clone();
+        request.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        // Calls log under the id of the RPC request, suffixed with their batch position
+        if let Some(id) = &self.request_id {
+            let id = match index {
+                Some(index) => format!("{}.{}", id, index),
+                None => id.clone(),
+            };
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                request.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+            }
+        }
+        if let Some(remote) = self.remote {
+            request.extensions_mut().insert(remote);
+        }
+        if let Some(cert) = &self.client_cert {
+            request.extensions_mut().insert(cert.clone());
+        }
+
+        // `Router` is always ready, so it can be called without `poll_ready`
+        let response = self.dispatcher.app.clone().call(request).await.unwrap_or_else(|e| match e {});
+        let bytes = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(|_| RpcError::new(INTERNAL_ERROR, "Failed to read response"))?;
+        let envelope: ApiResponse<Box<RawValue>> = serde_json::from_slice(&bytes)
+            .map_err(|_| RpcError::new(INTERNAL_ERROR, "Unexpected response"))?;
+
+        if envelope.success {
+            return envelope
+                .data
+                .map_or_else(|| serde_json::value::to_raw_value(&Value::Null), Ok)
+                .map_err(|_| RpcError::new(INTERNAL_ERROR, "Unexpected response"));
+        }
+        let code = envelope.code.unwrap_or(ErrorCode::Internal);
+        Err(RpcError {
+            code: rpc_code(code),
+            message: envelope.error.unwrap_or_default(),
+            data: Some(RpcErrorData { code, field: envelope.field, details: envelope.details }),
+        })
+    }
+}
+
+// A malformed call is answered with the id it carried, when that id is usable
+fn parse_call(call: Value) -> Result<RpcRequest, Box<RpcResponse>> {
+    let id = match call.get("id") {
+        Some(id @ (Value::String(_) | Value::Number(_) | Value::Null)) => id.clone(),
+        _ => Value::Null,
+    };
+    let invalid = |message: &str| Box::new(RpcResponse::error(id.clone(), RpcError::new(INVALID_REQUEST, message)));
+
+    let call: RpcRequest = serde_json::from_value(call).map_err(|_| invalid("Invalid request"))?;
+    if call.jsonrpc != "2.0" {
+        return Err(invalid("jsonrpc must be \"2.0\""));
+    }
+    if !matches!(call.id, None | Some(Value::String(_) | Value::Number(_) | Value::Null)) {
+        return Err(invalid("id must be a string, number or null"));
+    }
+    if !matches!(call.params, None | Some(Value::Object(_) | Value::Array(_))) {
+        return Err(invalid("params must be an object or an array"));
+    }
+    Ok(call)
+}
+
+// Fills path parameters (`:key_id` from `keyId` or `key_id`) and sends the params as the
+// body of methods that take one
+fn build_request(method: &Method, path: &str, params: Option<Value>) -> Result<(String, Vec<u8>), String> {
+    let mut uri = String::new();
+    for segment in path.split('/').skip(1) {
+        uri.push('/');
+        let Some(name) = segment.strip_prefix(':') else {
+            uri.push_str(segment);
+            continue;
+        };
+        let value = params
+            .as_ref()
+            .and_then(|params| params.get(camel_case(name)).or_else(|| params.get(name)));
+        match value {
+            Some(Value::String(value)) => uri.push_str(&encode_segment(value)),
+            Some(Value::Number(value)) => uri.push_str(&value.to_string()),
+            _ => return Err(format!("Missing parameter: {}", camel_case(name))),
+        }
+    }
+
+    let body = match (method, params) {
+        (&Method::GET | &Method::DELETE, _) | (_, None) => Vec::new(),
+        (_, Some(params)) => serde_json::to_vec(&params).unwrap_or_default(),
+    };
+    Ok((uri, body))
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+fn encode_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn rpc_code(code: ErrorCode) -> i64 {
+    match code {
+        ErrorCode::InvalidJson | ErrorCode::MissingField | ErrorCode::InvalidField | ErrorCode::InvalidRequest => {
+            INVALID_PARAMS
+        }
+        // Unknown paths only come from feature groups that are switched off
+        ErrorCode::NotFound => METHOD_NOT_FOUND,
+        ErrorCode::Internal => INTERNAL_ERROR,
+        _ => SERVER_ERROR,
+    }
+}
+
+// Everything the middleware reads from the caller (keys, forwarding headers) passes
+// through; the body's own headers and content negotiation do not
+fn forwarded_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut forwarded = headers.clone();
+    for name in [
+        header::CONTENT_LENGTH,
+        header::CONTENT_TYPE,
+        header::TRANSFER_ENCODING,
+        header::ACCEPT_ENCODING,
+        header::ORIGIN,
+        header::EXPECT,
+    ] {
+        forwarded.remove(name);
+    }
+    forwarded.remove(&REQUEST_ID_HEADER);
+    forwarded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use serde_json::json;
+    use solana_sdk::signer::keypair::Keypair;
+    use crate::routes;
+    use crate::signer::LocalSigner;
+    use crate::vault::MemoryKeyStore;
+
+    fn app() -> Router {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+            .with_routes(RouteTable::new(&routes::endpoints()));
+        let app = routes::versioned_router(routes::endpoints).with_state(state);
+        app.clone().layer(Extension(Dispatcher::new(app)))
+    }
+
+    async fn post(path: &str, body: impl Into<Body>) -> (StatusCode, Value) {
+        let request = Request::post(path).header(header::CONTENT_TYPE, "application/json").body(body.into()).unwrap();
+        let response = app().call(request).await.unwrap_or_else(|e| match e {});
+        let status = response.status();
+        let bytes = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    async fn rpc(payload: Value) -> (StatusCode, Value) {
+        post("/rpc", payload.to_string()).await
+    }
+
+    fn call(method: &str, params: Value, id: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": id })
+    }
+
+    #[tokio::test]
+    async fn single_call_returns_the_rest_data() {
+        let (status, response) = rpc(call("getRentExemptMinimum", json!({ "space": 0 }), json!(7))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["space"], 0);
+        assert!(response["result"]["lamports"].as_u64().unwrap() > 0);
+        assert!(response.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn v2_calls_get_v2_shapes() {
+        let secret = bs58::encode(Keypair::new().to_bytes()).into_string();
+        let sign = call("signMessage", json!({ "message": "hi", "secret": secret }), json!(1));
+
+        let (_, v1) = rpc(sign.clone()).await;
+        assert!(v1["result"]["public_key"].is_string());
+        let (_, v2) = post("/v2/rpc", sign.to_string()).await;
+        assert!(v2["result"]["publicKey"].is_string());
+    }
+
+    #[tokio::test]
+    async fn batches_answer_in_order_and_skip_notifications() {
+        let (status, response) = rpc(json!([
+            call("generateKeypair", json!({}), json!("a")),
+            { "jsonrpc": "2.0", "method": "generateKeypair" },
+            call("noSuchMethod", json!({}), json!("b")),
+        ]))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], "a");
+        assert!(responses[0]["result"]["pubkey"].is_string());
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn notifications_alone_get_no_content() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "generateKeypair" });
+        assert_eq!(rpc(notification.clone()).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(rpc(json!([notification.clone(), notification])).await.0, StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn request_errors() {
+        let (_, empty) = rpc(json!([])).await;
+        assert_eq!((empty["error"]["code"].as_i64(), &empty["id"]), (Some(INVALID_REQUEST), &Value::Null));
+
+        let (_, unknown) = rpc(call("noSuchMethod", json!({}), json!(3))).await;
+        assert_eq!((unknown["error"]["code"].as_i64(), &unknown["id"]), (Some(METHOD_NOT_FOUND), &json!(3)));
+
+        let (_, parse) = post("/rpc", "{not json").await;
+        assert_eq!(parse["error"]["code"], PARSE_ERROR);
+
+        let (_, version) = rpc(json!({ "jsonrpc": "1.0", "method": "listKeys", "id": 4 })).await;
+        assert_eq!((version["error"]["code"].as_i64(), &version["id"]), (Some(INVALID_REQUEST), &json!(4)));
+
+        let (_, params) = rpc(call("verifyMessage", json!({}), json!(5))).await;
+        assert_eq!(params["error"]["code"], INVALID_PARAMS);
+        assert_eq!(params["error"]["data"]["code"], "MISSING_FIELD");
+
+        let (_, path) = rpc(call("getKey", json!({}), json!(6))).await;
+        assert_eq!(path["error"], json!({ "code": INVALID_PARAMS, "message": "Missing parameter: keyId" }));
+    }
+
+    #[tokio::test]
+    async fn batch_size_is_limited() {
+        let batch = |len: usize| Value::Array((0..len).map(|id| call("listKeys", Value::Null, json!(id))).collect());
+
+        let (_, full) = rpc(batch(MAX_RPC_BATCH)).await;
+        assert_eq!(full.as_array().unwrap().len(), MAX_RPC_BATCH);
+
+        let (_, over) = rpc(batch(MAX_RPC_BATCH + 1)).await;
+        assert_eq!(over["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn path_parameters_are_filled_and_encoded() {
+        let (uri, body) = build_request(&Method::GET, "/keys/:key_id", Some(json!({ "keyId": "a b/c" }))).unwrap();
+        assert_eq!((uri.as_str(), body.len()), ("/keys/a%20b%2Fc", 0));
+
+        let (uri, _) = build_request(&Method::GET, "/rent/:space", Some(json!({ "space": 165 }))).unwrap();
+        assert_eq!(uri, "/rent/165");
+    }
+}
@@ -1,130 +1,102 @@
-use axum::{response::Json, body::Bytes};
+use axum::{extract::State, response::Json, body::Bytes};
 use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
 use spl_token::instruction::{initialize_mint, mint_to};
-use base64::Engine;
+use crate::error::{parse_json, ApiError, ApiErrorResponses, ApiResult, FieldResult};
+use crate::metrics::metrics;
 use crate::models::requests::{CreateTokenRequest, MintTokenRequest};
-use crate::models::responses::{ApiResponse, InstructionResponse, AccountMeta, TokenCreateResponse};
+use crate::models::instruction::ApiInstruction;
+use crate::models::responses::{ApiResponse, InstructionResponse, TokenCreateResponse, Versioned};
+use crate::routes::ApiVersion;
+use crate::state::AppState;
 use crate::utils::validation::{validate_pubkey, validate_decimals, validate_amount};
 
+#[utoipa::path(
+    post,
+    path = "/token/create",
+    tag = "token",
+    summary = "Create an SPL token initialize mint instruction",
+    request_body = CreateTokenRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<TokenCreateResponse>), ApiErrorResponses),
+)]
 pub async fn create_token(
+    State(state): State<AppState>,
+    version: ApiVersion,
     body: Bytes,
-) -> Json<ApiResponse<TokenCreateResponse>> {
+) -> ApiResult<Versioned<TokenCreateResponse, ApiInstruction>> {
     // Parse JSON manually
-    let request: CreateTokenRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+    let request: CreateTokenRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
 
     // Validate inputs
-    let mint_authority = match validate_pubkey(&request.payer) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let mint_authority = validate_pubkey(&request.payer).field("payer")?;
 
-    let mint = match validate_pubkey(&request.mint) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let mint = validate_pubkey(&request.mint).field("mint")?;
 
-    if let Err(e) = validate_decimals(request.decimals) {
-        return Json(ApiResponse::error(e));
-    }
+    validate_decimals(request.decimals).field("decimals")?;
 
     // Create initialize mint instruction
-    let instruction = match initialize_mint(
+    let instruction = initialize_mint(
         &spl_token::id(),
         &mint,
         &mint_authority,
         Some(&mint_authority), // freeze authority (using same as mint authority)
         request.decimals,
-    ) {
-        Ok(instr) => instr,
-        Err(e) => return Json(ApiResponse::error(format!("Failed to create instruction: {}", e))),
-    };
+    )
+    .map(|instruction| for_token_program(instruction, state.token_program))
+    .map_err(|e| ApiError::invalid_request(format!("Failed to create instruction: {}", e)))?;
 
-    let response = token_create_instruction_to_response(instruction);
-    Json(ApiResponse::success(response))
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, TokenCreateResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/token/mint",
+    tag = "token",
+    summary = "Create an SPL token mint-to instruction",
+    request_body = MintTokenRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<InstructionResponse>), ApiErrorResponses),
+)]
 pub async fn mint_token(
+    State(state): State<AppState>,
+    version: ApiVersion,
     body: Bytes,
-) -> Json<ApiResponse<InstructionResponse>> {
+) -> ApiResult<Versioned<InstructionResponse, ApiInstruction>> {
     // Parse JSON manually
-    let request: MintTokenRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+    let request: MintTokenRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
 
     // Validate inputs
-    let mint = match validate_pubkey(&request.mint) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let mint = validate_pubkey(&request.mint).field("mint")?;
 
-    let destination = match validate_pubkey(&request.destination) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let destination = validate_pubkey(&request.destination).field("destination")?;
 
-    let authority = match validate_pubkey(&request.authority) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let authority = validate_pubkey(&request.authority).field("authority")?;
 
-    if let Err(e) = validate_amount(request.amount) {
-        return Json(ApiResponse::error(e));
-    }
+    validate_amount(request.amount).field("amount")?;
 
     // Create mint to instruction
-    let instruction = match mint_to(
+    let instruction = mint_to(
         &spl_token::id(),
         &mint,
         &destination,
         &authority,
         &[&authority], // signers
         request.amount,
-    ) {
-        Ok(instr) => instr,
-        Err(e) => return Json(ApiResponse::error(format!("Failed to create instruction: {}", e))),
-    };
+    )
+    .map(|instruction| for_token_program(instruction, state.token_program))
+    .map_err(|e| ApiError::invalid_request(format!("Failed to create instruction: {}", e)))?;
 
-    let response = instruction_to_response(instruction);
-    Json(ApiResponse::success(response))
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, InstructionResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
 }
 
-fn instruction_to_response(instruction: Instruction) -> InstructionResponse {
-    let accounts = instruction
-        .accounts
-        .iter()
-        .map(|account| AccountMeta {
-            pubkey: account.pubkey.to_string(),
-            is_signer: account.is_signer,
-            is_writable: account.is_writable,
-        })
-        .collect();
-
-    InstructionResponse {
-        program_id: instruction.program_id.to_string(),
-        accounts,
-        instruction_data: base64::engine::general_purpose::STANDARD.encode(&instruction.data),
-    }
+/// Retargets an instruction built by `spl_token` (which only accepts its own program id)
+/// at the configured token program. Token-2022 uses the same layout for these instructions.
+pub(crate) fn for_token_program(mut instruction: Instruction, program_id: Pubkey) -> Instruction {
+    instruction.program_id = program_id;
+    instruction
 }
-
-fn token_create_instruction_to_response(instruction: Instruction) -> TokenCreateResponse {
-    // For token create, return the first account (mint account) as a single object
-    let mint_account = instruction.accounts.first().map(|account| AccountMeta {
-        pubkey: account.pubkey.to_string(),
-        is_signer: account.is_signer,
-        is_writable: account.is_writable,
-    }).unwrap_or_else(|| AccountMeta {
-        pubkey: "11111111111111111111111111111111".to_string(),
-        is_signer: false,
-        is_writable: true,
-    });
-
-    TokenCreateResponse {
-        program_id: instruction.program_id.to_string(),
-        accounts: mint_account,
-        instruction_data: base64::engine::general_purpose::STANDARD.encode(&instruction.data),
-    }
-} 
@@ -1,70 +1,72 @@
-use axum::{response::Json, body::Bytes};
-use solana_sdk::{instruction::Instruction, system_instruction};
+use axum::{extract::State, response::Json, body::Bytes};
+use solana_sdk::system_instruction;
 use spl_token::instruction::transfer;
-use base64::Engine;
+use crate::error::{parse_json, ApiError, ApiErrorResponses, ApiResult, FieldResult};
+use crate::metrics::metrics;
 use crate::models::requests::{SendSolRequest, SendTokenRequest};
-use crate::models::responses::{ApiResponse, InstructionResponse, AccountMeta, SolTransferResponse, TokenTransferResponse, TokenAccountMeta};
+use crate::models::instruction::ApiInstruction;
+use crate::models::responses::{ApiResponse, SolTransferResponse, TokenTransferResponse, Versioned};
+use crate::routes::ApiVersion;
+use crate::handlers::token::for_token_program;
+use crate::state::AppState;
 use crate::utils::validation::{validate_pubkey, validate_amount};
 
+#[utoipa::path(
+    post,
+    path = "/send/sol",
+    tag = "transfer",
+    summary = "Create a SOL transfer instruction",
+    request_body = SendSolRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<SolTransferResponse>), ApiErrorResponses),
+)]
 pub async fn send_sol(
+    version: ApiVersion,
     body: Bytes,
-) -> Json<ApiResponse<SolTransferResponse>> {
+) -> ApiResult<Versioned<SolTransferResponse, ApiInstruction>> {
     // Parse JSON manually
-    let request: SendSolRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+    let request: SendSolRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
 
     // Validate inputs
-    let from = match validate_pubkey(&request.from) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let from = validate_pubkey(&request.from).field("from")?;
 
-    let to = match validate_pubkey(&request.to) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let to = validate_pubkey(&request.to).field("to")?;
 
-    if let Err(e) = validate_amount(request.lamports) {
-        return Json(ApiResponse::error(e));
-    }
+    validate_amount(request.lamports).field("lamports")?;
 
     // Create SOL transfer instruction
     let instruction = system_instruction::transfer(&from, &to, request.lamports);
 
-    let response = sol_instruction_to_response(instruction);
-    Json(ApiResponse::success(response))
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, SolTransferResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/send/token",
+    tag = "transfer",
+    summary = "Create an SPL token transfer instruction",
+    request_body = SendTokenRequest,
+    responses((status = 200, description = "Result envelope; also carries errors unless `X-Error-Mode: http`. Under `/v2` the data is an `Instruction`", body = ApiResponse<TokenTransferResponse>), ApiErrorResponses),
+)]
 pub async fn send_token(
+    State(state): State<AppState>,
+    version: ApiVersion,
     body: Bytes,
-) -> Json<ApiResponse<TokenTransferResponse>> {
+) -> ApiResult<Versioned<TokenTransferResponse, ApiInstruction>> {
     // Parse JSON manually
-    let request: SendTokenRequest = match serde_json::from_slice(&body) {
-        Ok(req) => req,
-        Err(_) => return Json(ApiResponse::error("Missing required fields".to_string())),
-    };
+    let request: SendTokenRequest = parse_json(&body)?;
+    let output = request.output.check(version)?;
 
     // Validate inputs
-    let destination = match validate_pubkey(&request.destination) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let destination = validate_pubkey(&request.destination).field("destination")?;
 
-    let _mint = match validate_pubkey(&request.mint) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let _mint = validate_pubkey(&request.mint).field("mint")?;
 
-    let owner = match validate_pubkey(&request.owner) {
-        Ok(pubkey) => pubkey,
-        Err(e) => return Json(ApiResponse::error(e)),
-    };
+    let owner = validate_pubkey(&request.owner).field("owner")?;
 
-    if let Err(e) = validate_amount(request.amount) {
-        return Json(ApiResponse::error(e));
-    }
+    validate_amount(request.amount).field("amount")?;
 
     // For SPL token transfer, we need to derive the source token account
     // In a real scenario, you would need to find or derive the actual token accounts
@@ -72,67 +74,18 @@ pub async fn send_token(
     let source = owner; // This is simplified - normally you'd derive the associated token account
 
     // Create SPL token transfer instruction
-    let instruction = match transfer(
+    let instruction = transfer(
         &spl_token::id(),
         &source,
         &destination,
         &owner,
         &[&owner], // signers
         request.amount,
-    ) {
-        Ok(instr) => instr,
-        Err(e) => return Json(ApiResponse::error(format!("Failed to create instruction: {}", e))),
-    };
+    )
+    .map(|instruction| for_token_program(instruction, state.token_program))
+    .map_err(|e| ApiError::invalid_request(format!("Failed to create instruction: {}", e)))?;
 
-    let response = token_instruction_to_response(instruction);
-    Json(ApiResponse::success(response))
+    metrics().instruction_built(&instruction.program_id);
+    let response = version.shape(instruction, TokenTransferResponse::from, |ix| ApiInstruction::new(&ix, output));
+    Ok(Json(ApiResponse::success(response)))
 }
-
-fn sol_instruction_to_response(instruction: Instruction) -> SolTransferResponse {
-    let accounts = instruction
-        .accounts
-        .iter()
-        .map(|account| account.pubkey.to_string())
-        .collect();
-
-    SolTransferResponse {
-        program_id: instruction.program_id.to_string(),
-        accounts,
-        instruction_data: base64::engine::general_purpose::STANDARD.encode(&instruction.data),
-    }
-}
-
-fn token_instruction_to_response(instruction: Instruction) -> TokenTransferResponse {
-    let accounts = instruction
-        .accounts
-        .iter()
-        .map(|account| TokenAccountMeta {
-            pubkey: account.pubkey.to_string(),
-            is_signer: account.is_signer,
-        })
-        .collect();
-
-    TokenTransferResponse {
-        program_id: instruction.program_id.to_string(),
-        accounts,
-        instruction_data: base64::engine::general_purpose::STANDARD.encode(&instruction.data),
-    }
-}
-
-fn instruction_to_response(instruction: Instruction) -> InstructionResponse {
-    let accounts = instruction
-        .accounts
-        .iter()
-        .map(|account| AccountMeta {
-            pubkey: account.pubkey.to_string(),
-            is_signer: account.is_signer,
-            is_writable: account.is_writable,
-        })
-        .collect();
-
-    InstructionResponse {
-        program_id: instruction.program_id.to_string(),
-        accounts,
-        instruction_data: base64::engine::general_purpose::STANDARD.encode(&instruction.data),
-    }
-} 
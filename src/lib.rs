@@ -1,3 +1,17 @@
+pub mod auth;
+pub mod config;
+pub mod error;
 pub mod handlers;
+pub mod metrics;
+pub mod middleware;
 pub mod models;
-pub mod utils; 
+pub mod openapi;
+pub mod ratelimit;
+pub mod shutdown;
+pub mod routes;
+pub mod signer;
+pub mod state;
+pub mod tls;
+pub mod upstream;
+pub mod utils;
+pub mod vault;
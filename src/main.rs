@@ -1,131 +1,155 @@
-use axum::{
-    routing::{post, get, put, patch, delete, head, options},
-    Router,
-    response::Json,
-    http::StatusCode,
-};
-use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
 use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+use tokio::sync::oneshot;
 
-mod handlers;
-mod models;
-mod utils;
-
-use handlers::{keypair, message, token, transfer};
-use models::responses::ApiResponse;
+use solana_http_server::config::{Config, LogFormat};
+use solana_http_server::shutdown;
+use solana_http_server::ratelimit::RateLimiter;
+use solana_http_server::routes::{self, RouteTable};
+use solana_http_server::state::AppState;
+use solana_http_server::tls;
 
 #[tokio::main]
 async fn main() {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    // Configuration: --config <path> (or CONFIG_FILE), then environment overrides.
+    // --check-config validates it and exits without starting the server
+    let args: Vec<String> = env::args().skip(1).collect();
+    let check_only = args.iter().any(|arg| arg == "--check-config");
+    let config_path = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .map(|i| args.get(i + 1).cloned().unwrap_or_else(|| exit_with("--config requires a path")))
+        .or_else(|| env::var("CONFIG_FILE").ok())
+        .map(PathBuf::from);
+    let config = Config::load(config_path.as_deref())
+        .and_then(|config| config.validate().map(|_| config))
+        .unwrap_or_else(|e| exit_with(&format!("Invalid configuration: {}", e)));
+    if check_only {
+        config.check_key_vault().unwrap_or_else(|e| exit_with(&format!("Invalid configuration: {}", e)));
+        println!("Configuration OK");
+        return;
+    }
+
+    // Initialize logging: log_format = "json" emits one JSON object per line with the request span fields
+    match config.log_format().expect("validated") {
+        LogFormat::Json => tracing_subscriber::fmt().json().with_current_span(true).with_span_list(false).init(),
+        LogFormat::Text => tracing_subscriber::fmt::init(),
+    }
+
+    // Key vault backend: file:<path> (password from KEY_VAULT_PASSWORD) or in-memory
+    let vault = config.key_vault().unwrap_or_else(|e| exit_with(&e));
+
+    // Signing backend: unix:<path> or http://host:port for an external signer
+    let signer = config.signer_backend(vault.clone()).unwrap_or_else(|e| exit_with(&e));
+
+    // Error status mode: "http" sends real 4xx/5xx statuses by default;
+    // clients can still pick per request with the X-Error-Mode header
+    let mut state = AppState::new(vault, signer)
+        .with_status_mode(config.status_mode().expect("validated"))
+        .with_cluster(config.cluster().expect("validated"))
+        .with_token_program(config.token_program().expect("validated"))
+        .with_rent(config.rent().expect("validated"))
+        .with_limits(config.request_limits().expect("validated"))
+        .with_features(config.features.clone());
+
+    // API keys and mTLS client certificates: hashes and their scopes. Validation only
+    // allows neither with auth.disabled, which leaves every endpoint open
+    let api_keys = config.api_keys().expect("Failed to load API keys");
+    let client_certs = config.client_certs().expect("validated");
+    if config.auth.disabled {
+        tracing::warn!("Authentication is disabled; all endpoints are unauthenticated");
+    }
+    if let Some(api_keys) = api_keys {
+        state = state.with_api_keys(api_keys);
+    }
+    if let Some(client_certs) = client_certs {
+        state = state.with_client_certs(client_certs);
+    }
 
-    // Build our application with routes
-    let app = Router::new()
-        // Keypair operations
-        .route("/keypair", 
-            post(keypair::generate_keypair)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        
-        // Message operations
-        .route("/message/sign", 
-            post(message::sign_message)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        .route("/message/verify", 
-            post(message::verify_message)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        
-        // Token operations
-        .route("/token/create", 
-            post(token::create_token)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        .route("/token/mint", 
-            post(token::mint_token)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        
-        // Transfer operations
-        .route("/send/sol", 
-            post(transfer::send_sol)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        .route("/send/token", 
-            post(transfer::send_token)
-            .get(handle_method_error)
-            .put(handle_method_error)
-            .patch(handle_method_error)
-            .delete(handle_method_error)
-            .head(handle_method_error)
-            .options(handle_method_error)
-        )
-        
-        // Fallback for everything else
-        .fallback(handle_fallback)
-        
-        // Add middleware
-        .layer(
-            ServiceBuilder::new()
-                .layer(CorsLayer::permissive())
+    // Rate limiting: <n>/<s|m|h> per client, a separate limit for scrypt and batch endpoints,
+    // and a memory or redis://host:port store to share buckets between instances
+    if let Some((default, expensive)) = config.rate_limits().expect("validated") {
+        let store = config.rate_limit_store().unwrap_or_else(|e| exit_with(&e));
+        state = state.with_rate_limiter(
+            RateLimiter::new(store, default, expensive).with_trust_forwarded_for(config.rate_limit.trust_proxy),
         );
+    }
 
-    // Get port from environment variable or default to 3000
-    let port = env::var("PORT")
-        .unwrap_or_else(|_| "3000".to_string())
-        .parse::<u16>()
-        .expect("Invalid PORT value");
+    // RPC upstream: http(s)://host[:port] makes /readyz depend on the node's getHealth
+    if let Some(rpc) = config.rpc_upstream().expect("validated") {
+        state = state.with_rpc_upstream(rpc);
+    }
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port))
+    // Build our application from the route registry
+    state = state.with_routes(RouteTable::new(&routes::endpoints()));
+    let app = routes::app(&config, state.clone()).expect("validated");
+    // Plain-HTTP probes for the same state, so /readyz also fails while draining
+    let health = routes::router(
+        routes::endpoints().into_iter().filter(|endpoint| matches!(endpoint.path, "/healthz" | "/readyz")).collect(),
+    )
+    .with_state(state.clone());
+    let shutdown_state = state;
+
+    let addr = config.bind_addr().expect("validated");
+    let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("Failed to bind to address");
 
-    println!("🚀 Solana HTTP Server running on http://0.0.0.0:{}", port);
-    
-    // Start the server
-    axum::serve(listener, app)
-        .await
-        .expect("Failed to start server");
-}
+    // TLS: certificate files are polled and reloaded in place; HTTP/2 is offered over ALPN
+    let tls = config.tls().expect("validated").map(|settings| {
+        let (acceptor, reloader) = tls::acceptor(&settings).expect("Failed to load TLS certificate");
+        reloader.watch(settings.reload_interval);
+        acceptor
+    });
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    tracing::info!("🚀 Solana HTTP Server running on {}://{}", scheme, addr);
 
-// Handle both 404s and method not allowed with same response format
-async fn handle_fallback() -> (StatusCode, Json<ApiResponse<String>>) {
-    (StatusCode::OK, Json(ApiResponse::error("Endpoint not found".to_string())))
+    // Stops with the process; probes that fail mid-drain are expected
+    if let Some(health_addr) = config.health_bind_addr().expect("validated") {
+        let listener = tokio::net::TcpListener::bind(health_addr)
+            .await
+            .expect("Failed to bind the health address");
+        tracing::info!("Health checks on http://{}", health_addr);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, health).await {
+                tracing::error!(error = %e, "Health listener failed");
+            }
+        });
+    }
+    
+    // Start the server. On SIGTERM/SIGINT it fails readiness and keeps serving for the
+    // shutdown delay, then stops accepting connections and waits for in-flight requests,
+    // for at most the shutdown timeout
+    let (drain_started, drain_rx) = oneshot::channel();
+    let shutdown_requested =
+        shutdown::requested(shutdown_state, shutdown::signal(), config.shutdown_delay(), drain_started);
+    let server = async move {
+        match tls {
+            Some(acceptor) => tls::serve(listener, acceptor, app, shutdown_requested).await,
+            None => {
+                axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                    .with_graceful_shutdown(shutdown_requested)
+                    .await
+            }
+        }
+    };
+    let dropped = tokio::select! {
+        result = server => {
+            result.expect("Failed to start server");
+            0
+        }
+        dropped = shutdown::drain_deadline(drain_rx, config.shutdown_timeout()) => {
+            tracing::warn!(dropped, "Shutdown timeout passed; dropping remaining requests");
+            dropped
+        }
+    };
+    shutdown::finish(dropped);
 }
 
-// Handle wrong methods on valid endpoints - return HTTP 200 with JSON error
-async fn handle_method_error() -> (StatusCode, Json<ApiResponse<String>>) {
-    (StatusCode::OK, Json(ApiResponse::error("Method not allowed".to_string())))
+// Configuration errors go to stderr, also when logging is not set up yet
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
 }
@@ -0,0 +1,150 @@
+use std::sync::OnceLock;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use solana_sdk::pubkey::Pubkey;
+use crate::config::TOKEN_2022_PROGRAM_ID;
+
+/// Process-wide Prometheus metrics, exposed on `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub http_requests_in_flight: IntGauge,
+    pub http_errors: IntCounterVec,
+    pub keypairs_generated: IntCounterVec,
+    pub signatures_produced: IntCounterVec,
+    pub signatures_verified: IntCounterVec,
+    pub instructions_built: IntCounterVec,
+}
+
+/// Returns the global metrics, registering them on first use.
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("solana_server".to_string()), None)
+            .expect("valid metrics prefix");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route, method and status"),
+            &["route", "method", "status"],
+        )
+        .expect("valid metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route and method")
+                .buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+            &["route", "method"],
+        )
+        .expect("valid metric");
+        let http_requests_in_flight =
+            IntGauge::new("http_requests_in_flight", "HTTP requests currently being handled")
+                .expect("valid metric");
+        let http_errors = IntCounterVec::new(
+            Opts::new("http_errors_total", "API errors by route and error code"),
+            &["route", "code"],
+        )
+        .expect("valid metric");
+        let keypairs_generated = IntCounterVec::new(
+            Opts::new("keypairs_generated_total", "Keypairs generated, by how the secret was returned"),
+            &["storage"],
+        )
+        .expect("valid metric");
+        let signatures_produced = IntCounterVec::new(
+            Opts::new("signatures_produced_total", "Signatures produced by signature scheme"),
+            &["scheme"],
+        )
+        .expect("valid metric");
+        let signatures_verified = IntCounterVec::new(
+            Opts::new("signatures_verified_total", "Signature verifications by result"),
+            &["result"],
+        )
+        .expect("valid metric");
+        let instructions_built = IntCounterVec::new(
+            Opts::new("instructions_built_total", "Instructions built by target program"),
+            &["program"],
+        )
+        .expect("valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(http_requests_in_flight.clone()),
+            Box::new(http_errors.clone()),
+            Box::new(keypairs_generated.clone()),
+            Box::new(signatures_produced.clone()),
+            Box::new(signatures_verified.clone()),
+            Box::new(instructions_built.clone()),
+        ] {
+            registry.register(collector).expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            http_requests_in_flight,
+            http_errors,
+            keypairs_generated,
+            signatures_produced,
+            signatures_verified,
+            instructions_built,
+        }
+    }
+
+    pub fn keypair_generated(&self, storage: &str) {
+        self.keypairs_generated.with_label_values(&[storage]).inc();
+    }
+
+    pub fn signature_produced(&self, scheme: &str) {
+        self.signatures_produced.with_label_values(&[scheme]).inc();
+    }
+
+    pub fn signature_verified(&self, valid: bool) {
+        let result = if valid { "valid" } else { "invalid" };
+        self.signatures_verified.with_label_values(&[result]).inc();
+    }
+
+    pub fn instruction_built(&self, program_id: &Pubkey) {
+        self.instructions_built.with_label_values(&[program_name(program_id)]).inc();
+    }
+
+    /// Total HTTP requests completed across all routes.
+    pub fn requests_served(&self) -> u64 {
+        use prometheus::core::Collector;
+        self.http_requests
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| metric.get_counter().get_value() as u64)
+            .sum()
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into a Vec cannot fail for well-formed metric families
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+// Program labels are a fixed set so arbitrary ids cannot blow up cardinality
+fn program_name(program_id: &Pubkey) -> &'static str {
+    if *program_id == solana_sdk::system_program::id() {
+        "system"
+    } else if *program_id == spl_token::id() {
+        "spl_token"
+    } else if program_id.to_string() == TOKEN_2022_PROGRAM_ID {
+        "spl_token_2022"
+    } else if *program_id == solana_sdk::ed25519_program::id() {
+        "ed25519"
+    } else if *program_id == solana_sdk::secp256k1_program::id() {
+        "secp256k1"
+    } else {
+        "other"
+    }
+}
@@ -0,0 +1,27 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, Method},
+    middleware::Next,
+    response::Response,
+};
+use crate::state::AppState;
+
+/// Adds the route's `Allow` header to `OPTIONS` responses. The CORS layer answers every
+/// `OPTIONS` request itself, so the router's own `OPTIONS` handler is not reached while
+/// it is installed.
+pub async fn advertise_methods(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let allow = match (request.method(), request.extensions().get::<MatchedPath>()) {
+        (&Method::OPTIONS, Some(route)) => state.routes.allow(route.as_str()).cloned(),
+        _ => None,
+    };
+
+    let mut response = next.run(request).await;
+    if let Some(allow) = allow {
+        response.headers_mut().entry(header::ALLOW).or_insert(allow);
+    }
+    response
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderName},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use crate::error::{ApiError, ErrorCode};
+use crate::state::AppState;
+use crate::tls::ClientCertificate;
+
+pub static API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Endpoint groups an API key can be granted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Keypair,
+    Message,
+    Token,
+    Transfer,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Keypair, Scope::Message, Scope::Token, Scope::Transfer];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Keypair => "keypair",
+            Scope::Message => "message",
+            Scope::Token => "token",
+            Scope::Transfer => "transfer",
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keypair" => Ok(Scope::Keypair),
+            "message" => Ok(Scope::Message),
+            "token" => Ok(Scope::Token),
+            "transfer" => Ok(Scope::Transfer),
+            other => Err(format!("Unknown API key scope: {}", other)),
+        }
+    }
+}
+
+/// Caller identity attached to authenticated requests as a request extension.
+#[derive(Debug, Clone)]
+pub struct ApiIdentity {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl ApiIdentity {
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// API keys indexed by the hex SHA-256 of the key; plaintext keys are never stored.
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: HashMap<String, ApiIdentity>,
+}
+
+impl ApiKeys {
+    /// Loads a keys file with one `<name> <sha256 hex> <scope>[,<scope>...]` entry per line.
+    /// `*` grants every scope; blank lines and `#` comments are ignored.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read API keys file {}: {}", path, e))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut api_keys = ApiKeys::default();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (name, hash, scopes) = match fields.as_slice() {
+                [name, hash, scopes] => (*name, *hash, *scopes),
+                _ => return Err(format!("API keys line {}: expected `<name> <sha256> <scopes>`", number + 1)),
+            };
+            api_keys.insert(name, hash, scopes).map_err(|e| format!("API keys line {}: {}", number + 1, e))?;
+        }
+
+        Ok(api_keys)
+    }
+
+    pub fn insert(&mut self, name: &str, hash: &str, scopes: &str) -> Result<(), String> {
+        let hash = hash.to_ascii_lowercase();
+        if hash.len() != 64 || hex::decode(&hash).is_err() {
+            return Err("key hash must be 64 hex characters".to_string());
+        }
+
+        let scopes = if scopes == "*" {
+            Scope::ALL.to_vec()
+        } else {
+            scopes.split(',').map(str::parse).collect::<Result<Vec<Scope>, String>>()?
+        };
+
+        let identity = ApiIdentity { name: name.to_string(), scopes };
+        if self.keys.insert(hash, identity).is_some() {
+            return Err("duplicate key hash".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn identify(&self, key: &str) -> Option<&ApiIdentity> {
+        self.keys.get(&hash_key(key))
+    }
+
+    /// Looks up an identity by digest directly, e.g. a client certificate fingerprint.
+    pub fn identify_digest(&self, digest: &str) -> Option<&ApiIdentity> {
+        self.keys.get(&digest.to_ascii_lowercase())
+    }
+}
+
+/// Hex SHA-256 of an API key, as stored in the keys file.
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Rejects requests to scoped endpoints without a key granting that scope, and attaches
+/// the caller's `ApiIdentity` to the request otherwise. Over mTLS, a known client
+/// certificate stands in for the key when none is sent.
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if state.api_keys.is_none() && state.client_certs.is_none() {
+        return next.run(request).await;
+    }
+    // Scopes come from the route registry; unmatched paths fall through to the 404
+    let scope = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|route| state.routes.scope(route.as_str(), request.method()));
+    let Some(scope) = scope else {
+        return next.run(request).await;
+    };
+
+    let identity = match presented_key(request.headers()) {
+        Some(key) => state.api_keys.as_ref().and_then(|api_keys| api_keys.identify(key)),
+        None => match (request.extensions().get::<ClientCertificate>(), state.client_certs.as_ref()) {
+            (Some(cert), Some(client_certs)) => match client_certs.identify_digest(&cert.fingerprint) {
+                Some(identity) => Some(identity),
+                None => {
+                    return ApiError::new(ErrorCode::Unauthorized, "Unknown client certificate").into_response()
+                }
+            },
+            _ => return ApiError::new(ErrorCode::Unauthorized, "Missing API key").into_response(),
+        },
+    };
+    let Some(identity) = identity else {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response();
+    };
+
+    if !identity.allows(scope) {
+        tracing::warn!(identity = %identity.name, scope = scope.as_str(), "API key lacks scope");
+        return ApiError::new(ErrorCode::Forbidden, format!("API key is not allowed to use {} endpoints", scope.as_str()))
+            .into_response();
+    }
+
+    tracing::debug!(identity = %identity.name, path = request.uri().path(), "authenticated request");
+    request.extensions_mut().insert(identity.clone());
+    next.run(request).await
+}
+
+// Accepts `X-API-Key: <key>` or `Authorization: Bearer <key>`
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(&API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        return Some(key.trim());
+    }
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use axum::body::Body;
+    use axum::http::{Method, StatusCode};
+    use axum::middleware;
+    use tower::Service;
+    use crate::routes::{self, RouteTable};
+    use crate::signer::LocalSigner;
+    use crate::vault::MemoryKeyStore;
+
+    const CERT_DER: &[u8] = b"not really a certificate";
+
+    fn keys_file() -> String {
+        format!(
+            "# name hash scopes\n\nadmin {} *\nsigner {} message\n",
+            hash_key("admin-key"),
+            hash_key("signer-key"),
+        )
+    }
+
+    async fn send(request: axum::http::request::Builder, cert: Option<&[u8]>) -> StatusCode {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let mut client_certs = ApiKeys::default();
+        client_certs.insert("automation", &ClientCertificate::from_der(CERT_DER).fingerprint, "keypair").unwrap();
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+            .with_routes(RouteTable::new(&routes::endpoints()))
+            .with_api_keys(ApiKeys::parse(&keys_file()).unwrap())
+            .with_client_certs(client_certs);
+        let mut app = routes::versioned_router(routes::endpoints)
+            .layer(middleware::from_fn_with_state(state.clone(), require_api_key))
+            .with_state(state);
+
+        let mut request = request.body(Body::from("{}")).unwrap();
+        if let Some(der) = cert {
+            request.extensions_mut().insert(ClientCertificate::from_der(der));
+        }
+        app.call(request).await.unwrap_or_else(|e| match e {}).status()
+    }
+
+    fn post(path: &str) -> axum::http::request::Builder {
+        Request::builder().method(Method::POST).uri(path)
+    }
+
+    #[tokio::test]
+    async fn scoped_endpoints_need_a_valid_key() {
+        assert_eq!(send(post("/keypair"), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(post("/v2/keypair").header(&API_KEY_HEADER, "guess"), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(post("/keypair").header(&API_KEY_HEADER, "admin-key"), None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn keys_are_limited_to_their_scopes() {
+        let signer = |path: &str| post(path).header(&API_KEY_HEADER, "signer-key");
+        assert_eq!(send(signer("/keypair"), None).await, StatusCode::FORBIDDEN);
+        assert_eq!(send(signer("/send/sol"), None).await, StatusCode::FORBIDDEN);
+        // Past authentication, the empty body fails validation instead
+        assert_eq!(send(signer("/message/verify"), None).await, StatusCode::BAD_REQUEST);
+
+        // `*` grants every scope
+        for path in ["/keypair", "/message/verify", "/token/create", "/send/sol"] {
+            let status = send(post(path).header(&API_KEY_HEADER, "admin-key"), None).await;
+            assert!(status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN, "{}: {}", path, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn bearer_tokens_work_and_the_api_key_header_wins() {
+        let bearer = |key: &str| post("/keypair").header(header::AUTHORIZATION, format!("Bearer {}", key));
+        assert_eq!(send(bearer("admin-key"), None).await, StatusCode::OK);
+        assert_eq!(send(bearer("admin-key").header(&API_KEY_HEADER, "guess"), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(bearer("guess").header(&API_KEY_HEADER, "admin-key"), None).await, StatusCode::OK);
+        let basic = post("/keypair").header(header::AUTHORIZATION, "Basic YWRtaW4ta2V5");
+        assert_eq!(send(basic, None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn client_certificates_map_to_identities() {
+        assert_eq!(send(post("/keypair"), Some(CERT_DER)).await, StatusCode::OK);
+        assert_eq!(send(post("/message/verify"), Some(CERT_DER)).await, StatusCode::FORBIDDEN);
+        assert_eq!(send(post("/keypair"), Some(b"someone else")).await, StatusCode::UNAUTHORIZED);
+        // A key sent alongside an unknown certificate takes precedence
+        assert_eq!(send(post("/keypair").header(&API_KEY_HEADER, "admin-key"), Some(b"someone else")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn unscoped_endpoints_stay_open() {
+        assert_eq!(send(Request::builder().uri("/healthz"), None).await, StatusCode::OK);
+        assert_eq!(send(Request::builder().uri("/v1/rent/0"), None).await, StatusCode::OK);
+        assert_eq!(send(Request::builder().uri("/openapi.json"), None).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn keys_files_are_validated() {
+        let hash = hash_key("key");
+        assert_eq!(ApiKeys::parse(&keys_file()).unwrap().identify("signer-key").unwrap().scopes, [Scope::Message]);
+        assert!(ApiKeys::parse("").unwrap().is_empty());
+
+        for contents in [
+            format!("name {} keypair extra", hash),
+            format!("name {}", hash),
+            format!("name {} keypair", &hash[..63]),
+            format!("name {}zz keypair", &hash[..62]),
+            format!("name {} teleport", hash),
+            format!("name {} keypair,", hash),
+            format!("a {} keypair\nb {} message", hash, hash.to_uppercase()),
+        ] {
+            assert!(ApiKeys::parse(&contents).is_err(), "{}", contents);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::error::{ApiError, ErrorCode};
+use crate::routes::unversioned;
+use crate::state::AppState;
+
+/// Answers requests to endpoint groups switched off in `[features]` exactly like
+/// requests to paths that do not exist.
+pub async fn require_feature(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.features.enabled(unversioned(request.uri().path())) {
+        return ApiError::new(ErrorCode::NotFound, "Endpoint not found").into_response();
+    }
+    next.run(request).await
+}
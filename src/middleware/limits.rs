@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use axum::{
+    body::{self, Body},
+    extract::{MatchedPath, Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::LengthLimitError;
+use crate::error::{ApiError, ErrorCode};
+use crate::routes;
+use crate::state::AppState;
+
+/// Body size and time budgets applied to every request.
+#[derive(Debug, Clone)]
+pub struct RequestLimits {
+    pub max_body_bytes: usize,
+    default_timeout: Duration,
+    // Keyed by route pattern, e.g. `/keys/:key_id`
+    route_timeouts: HashMap<String, Duration>,
+}
+
+impl RequestLimits {
+    pub fn new(max_body_bytes: usize, default_timeout: Duration) -> Self {
+        Self {
+            max_body_bytes,
+            default_timeout,
+            route_timeouts: HashMap::new(),
+        }
+    }
+
+    pub fn with_route_timeout(mut self, route: &str, timeout: Duration) -> Self {
+        self.route_timeouts.insert(route.to_string(), timeout);
+        self
+    }
+
+    /// `route` may carry a `/v1` or `/v2` prefix; timeouts are configured per unversioned route.
+    pub fn timeout_for(&self, route: Option<&str>) -> Duration {
+        route
+            .and_then(|route| self.route_timeouts.get(routes::unversioned(route)))
+            .copied()
+            .unwrap_or(self.default_timeout)
+    }
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self::new(2 * 1024 * 1024, Duration::from_secs(30))
+    }
+}
+
+/// Fails requests that outlive their route's timeout. The handler future is dropped;
+/// work already handed to a blocking thread (scrypt) runs to completion unobserved.
+pub async fn enforce_timeout(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string());
+    let timeout = state.limits.timeout_for(route.as_deref());
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ApiError::new(ErrorCode::Timeout, "Request timed out").into_response(),
+    }
+}
+
+/// Buffers the body up to `max_body_bytes` so handlers never read an unbounded one,
+/// answering larger bodies with a `PAYLOAD_TOO_LARGE` error.
+pub async fn limit_body(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let max = state.limits.max_body_bytes;
+    let too_large = || {
+        ApiError::new(ErrorCode::PayloadTooLarge, format!("Request body exceeds {} bytes", max)).into_response()
+    };
+
+    // Reject up front when the client announces the size
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max as u64) {
+        return too_large();
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match body::to_bytes(body, max).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let err = err.into_inner();
+            if err.downcast_ref::<LengthLimitError>().is_some() {
+                return too_large();
+            }
+            return ApiError::invalid_request(format!("Failed to read request body: {}", err)).into_response();
+        }
+    };
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_timeouts_apply_to_every_version() {
+        let limits = RequestLimits::new(1024, Duration::from_secs(30))
+            .with_route_timeout("/keys/:key_id", Duration::from_secs(5));
+
+        for route in ["/keys/:key_id", "/v1/keys/:key_id", "/v2/keys/:key_id"] {
+            assert_eq!(limits.timeout_for(Some(route)), Duration::from_secs(5), "{}", route);
+        }
+        assert_eq!(limits.timeout_for(Some("/v3/keys/:key_id")), Duration::from_secs(30));
+        assert_eq!(limits.timeout_for(None), Duration::from_secs(30));
+    }
+}
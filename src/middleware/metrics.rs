@@ -0,0 +1,70 @@
+use std::time::Instant;
+use axum::{
+    extract::{MatchedPath, Request},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use crate::error::ErrorCode;
+use crate::metrics::metrics;
+
+/// Records request count, latency and API error codes per matched route.
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    // Label by route template, not the raw path, to keep key ids out of the label set
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = method_label(request.method());
+
+    let started = Instant::now();
+    let in_flight = InFlight::start();
+    let response = next.run(request).await;
+    drop(in_flight);
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let metrics = metrics();
+    metrics
+        .http_requests
+        .with_label_values(&[&route, method, response.status().as_str()])
+        .inc();
+    metrics
+        .http_request_duration
+        .with_label_values(&[&route, method])
+        .observe(elapsed);
+    if let Some(code) = response.extensions().get::<ErrorCode>() {
+        metrics.http_errors.with_label_values(&[&route, code.as_str()]).inc();
+    }
+
+    response
+}
+
+// Decrements on drop, so requests cut off by a timeout or shutdown are not left counted
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        metrics().http_requests_in_flight.inc();
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        metrics().http_requests_in_flight.dec();
+    }
+}
+
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::PATCH => "PATCH",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        _ => "OTHER",
+    }
+}
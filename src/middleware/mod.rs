@@ -0,0 +1,8 @@
+pub mod allow;
+pub mod api_key;
+pub mod features;
+pub mod limits;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod status_mode;
@@ -0,0 +1,82 @@
+use std::net::SocketAddr;
+use axum::{
+    body::{self, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::error::{ApiError, ErrorCode};
+use crate::middleware::api_key::ApiIdentity;
+use crate::ratelimit::EndpointClass;
+use crate::routes::unversioned;
+use crate::state::AppState;
+
+// Orchestrator probes share an address and must never be throttled into a restart
+const EXEMPT_PATHS: &[&str] = &["/healthz", "/readyz"];
+
+/// Applies the token bucket of the calling client: the API key identity when there is
+/// one, the client IP otherwise. Store failures let the request through.
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_ref() else {
+        return next.run(request).await;
+    };
+    let path = unversioned(request.uri().path());
+    if EXEMPT_PATHS.contains(&path) {
+        return next.run(request).await;
+    }
+
+    let client = client_key(&request, limiter.trust_forwarded_for());
+    let (class, request) = if EndpointClass::depends_on_body(path) {
+        // `limit_body` has already buffered and capped the body
+        let path = path.to_string();
+        let (parts, body) = request.into_parts();
+        let bytes = body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+        (EndpointClass::for_request(&path, &bytes), Request::from_parts(parts, Body::from(bytes)))
+    } else {
+        (EndpointClass::for_path(path), request)
+    };
+
+    match limiter.check(&client, class).await {
+        Ok(decision) if decision.allowed => next.run(request).await,
+        Ok(decision) => {
+            let retry_secs = (decision.retry_after.as_millis() as u64).div_ceil(1000).max(1);
+            let mut response = ApiError::new(ErrorCode::RateLimited, "Rate limit exceeded").into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_secs));
+            response
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "rate limit check failed; allowing request");
+            next.run(request).await
+        }
+    }
+}
+
+fn client_key(request: &Request, trust_forwarded_for: bool) -> String {
+    if let Some(identity) = request.extensions().get::<ApiIdentity>() {
+        return format!("key:{}", identity.name);
+    }
+
+    // Behind a reverse proxy the peer address is the proxy's; use the client it reports
+    if trust_forwarded_for {
+        let forwarded = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        if let Some(ip) = forwarded {
+            return format!("ip:{}", ip);
+        }
+    }
+
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
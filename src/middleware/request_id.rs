@@ -0,0 +1,117 @@
+use std::time::Instant;
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use rand::{rngs::OsRng, RngCore};
+use tracing::Instrument;
+use crate::error::ErrorCode;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// Longer client ids are replaced rather than echoed into every log line
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the current request, taken from `X-Request-Id` or generated. Available to
+/// handlers as a request extension.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Runs the request inside a tracing span carrying its id, method and route, logs the
+/// outcome and latency once it completes, and echoes the id in `X-Request-Id`.
+pub async fn trace_request(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid_request_id(v))
+        .map(str::to_string)
+        .unwrap_or_else(generate_request_id);
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        route = %route,
+    );
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let outcome = response
+        .extensions()
+        .get::<ErrorCode>()
+        .map(|code| code.as_str())
+        .unwrap_or("ok");
+    span.in_scope(|| {
+        tracing::info!(status = response.status().as_u16(), outcome, latency_ms, "request completed");
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+fn generate_request_id() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{self, Body};
+    use axum::{middleware, routing, Extension, Router};
+    use tower::Service;
+
+    // Sends a request through `trace_request` to a handler that returns the id it saw;
+    // the `X-Request-Id` response header and the handler's id
+    async fn send(request_id: Option<&str>) -> (String, String) {
+        let mut app = Router::new()
+            .route("/", routing::get(|Extension(RequestId(id)): Extension<RequestId>| async move { id }))
+            .layer(middleware::from_fn(trace_request));
+        let mut request = Request::builder().uri("/");
+        if let Some(id) = request_id {
+            request = request.header(&REQUEST_ID_HEADER, id);
+        }
+        let response = app.call(request.body(Body::empty()).unwrap()).await.unwrap_or_else(|e| match e {});
+        let header = response.headers()[&REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn incoming_ids_are_propagated() {
+        assert_eq!(send(Some("abc-123")).await, ("abc-123".to_string(), "abc-123".to_string()));
+    }
+
+    #[tokio::test]
+    async fn missing_or_unusable_ids_are_generated() {
+        let too_long = "x".repeat(MAX_REQUEST_ID_LEN + 1);
+        let mut seen = Vec::new();
+        for incoming in [None, Some(""), Some("has spaces"), Some(too_long.as_str())] {
+            let (header, handler) = send(incoming).await;
+            assert_eq!(header, handler);
+            assert_eq!(header.len(), 32, "{:?}", incoming);
+            assert!(header.bytes().all(|b| b.is_ascii_hexdigit()));
+            seen.push(header);
+        }
+        seen.dedup();
+        assert_eq!(seen.len(), 4);
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use crate::error::ErrorCode;
+use crate::state::AppState;
+
+/// Per-request override of the status mode: `X-Error-Mode: http` or `X-Error-Mode: legacy`.
+pub static ERROR_MODE_HEADER: HeaderName = HeaderName::from_static("x-error-mode");
+
+/// How API errors are reported at the HTTP level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusMode {
+    /// Always HTTP 200 with `success: false` in the body (the original contract).
+    #[default]
+    Legacy,
+    /// Real 4xx/5xx statuses matching the error code.
+    Http,
+}
+
+impl StatusMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "legacy" => Some(StatusMode::Legacy),
+            "http" => Some(StatusMode::Http),
+            _ => None,
+        }
+    }
+}
+
+/// Downgrades API error responses to 200 unless the client opted into HTTP statuses.
+pub async fn apply_status_mode(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mode = request
+        .headers()
+        .get(&ERROR_MODE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(StatusMode::parse)
+        .unwrap_or(state.status_mode);
+
+    let mut response = next.run(request).await;
+    if mode == StatusMode::Legacy && response.extensions().get::<ErrorCode>().is_some() {
+        *response.status_mut() = StatusCode::OK;
+    }
+    response
+}
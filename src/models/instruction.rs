@@ -0,0 +1,345 @@
+//! The one representation of a built instruction, and every conversion into it. `/v2`
+//! serves `ApiInstruction` as-is; the unversioned and `/v1` paths keep their historical
+//! per-endpoint shapes, which are derived from the same `Instruction` here.
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_utils::limited_deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction::SystemInstruction;
+use spl_token::instruction::TokenInstruction;
+use utoipa::ToSchema;
+use crate::config::TOKEN_2022_PROGRAM_ID;
+use crate::error::ApiError;
+use crate::models::responses::{
+    self, SolTransferResponse, TokenAccountMeta, TokenCreateResponse, TokenTransferResponse,
+};
+use crate::routes::ApiVersion;
+use crate::utils::encoding::DataEncoding;
+
+/// Output options accepted by every instruction builder. The unversioned and `/v1`
+/// paths always return base64 data without a decoded view, and refuse anything else.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, ToSchema)]
+pub struct InstructionOptions {
+    /// Encoding of `instructionData`.
+    #[serde(default, rename = "dataEncoding", alias = "data_encoding")]
+    pub data_encoding: DataEncoding,
+    /// Include `decoded`, the instruction data parsed into named fields.
+    #[serde(default)]
+    pub decoded: bool,
+}
+
+impl InstructionOptions {
+    /// Fails for options the historical shapes of `version` cannot carry.
+    pub fn check(self, version: ApiVersion) -> Result<Self, ApiError> {
+        if version == ApiVersion::V1 {
+            if self.data_encoding != DataEncoding::Base64 {
+                return Err(ApiError::invalid_field("dataEncoding", "dataEncoding is only supported under /v2"));
+            }
+            if self.decoded {
+                return Err(ApiError::invalid_field("decoded", "decoded is only supported under /v2"));
+            }
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = Instruction)]
+pub struct ApiInstruction {
+    pub program_id: String,
+    pub accounts: Vec<AccountMeta>,
+    pub instruction_data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<DecodedInstruction>,
+}
+
+/// Instruction data parsed into named fields, in the style of the RPC `jsonParsed`
+/// encoding. Only the instructions this server builds are recognised.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DecodedInstruction {
+    /// `system`, `spl-token`, `spl-token-2022`, `ed25519` or `secp256k1`.
+    pub program: String,
+    #[serde(rename = "type")]
+    pub instruction_type: String,
+    #[schema(value_type = Object)]
+    pub info: serde_json::Value,
+}
+
+impl ApiInstruction {
+    pub fn new(instruction: &Instruction, options: InstructionOptions) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: account_metas(instruction),
+            instruction_data: options.data_encoding.encode(&instruction.data),
+            decoded: options.decoded.then(|| decode(instruction)).flatten(),
+        }
+    }
+}
+
+fn account_metas(instruction: &Instruction) -> Vec<AccountMeta> {
+    instruction
+        .accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.pubkey.to_string(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect()
+}
+
+fn legacy_data(instruction: &Instruction) -> String {
+    DataEncoding::Base64.encode(&instruction.data)
+}
+
+// Historical shapes of the unversioned and /v1 endpoints; keep them byte-for-byte stable
+
+impl From<Instruction> for responses::InstructionResponse {
+    fn from(instruction: Instruction) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: account_metas(&instruction)
+                .into_iter()
+                .map(|account| responses::AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            instruction_data: legacy_data(&instruction),
+        }
+    }
+}
+
+impl From<Instruction> for SolTransferResponse {
+    fn from(instruction: Instruction) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction.accounts.iter().map(|account| account.pubkey.to_string()).collect(),
+            instruction_data: legacy_data(&instruction),
+        }
+    }
+}
+
+impl From<Instruction> for TokenTransferResponse {
+    fn from(instruction: Instruction) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account| TokenAccountMeta {
+                    pubkey: account.pubkey.to_string(),
+                    is_signer: account.is_signer,
+                })
+                .collect(),
+            instruction_data: legacy_data(&instruction),
+        }
+    }
+}
+
+impl From<Instruction> for TokenCreateResponse {
+    fn from(instruction: Instruction) -> Self {
+        // Only the first account (the mint), as a single object
+        let mint_account = instruction.accounts.first().map(|account| responses::AccountMeta {
+            pubkey: account.pubkey.to_string(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }).unwrap_or_else(|| responses::AccountMeta {
+            pubkey: "11111111111111111111111111111111".to_string(),
+            is_signer: false,
+            is_writable: true,
+        });
+
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: mint_account,
+            instruction_data: legacy_data(&instruction),
+        }
+    }
+}
+
+fn decode(instruction: &Instruction) -> Option<DecodedInstruction> {
+    let program_id = &instruction.program_id;
+    let account = |index: usize| instruction.accounts.get(index).map(|meta| meta.pubkey.to_string());
+
+    let (program, instruction_type, info) = if *program_id == solana_sdk::system_program::id() {
+        match limited_deserialize(&instruction.data).ok()? {
+            SystemInstruction::Transfer { lamports } => (
+                "system",
+                "transfer",
+                json!({ "source": account(0), "destination": account(1), "lamports": lamports }),
+            ),
+            _ => return None,
+        }
+    } else if *program_id == spl_token::id() || program_id.to_string() == TOKEN_2022_PROGRAM_ID {
+        let program = if *program_id == spl_token::id() { "spl-token" } else { "spl-token-2022" };
+        match TokenInstruction::unpack(&instruction.data).ok()? {
+            TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority } => (
+                program,
+                "initializeMint",
+                json!({
+                    "mint": account(0),
+                    "rentSysvar": account(1),
+                    "decimals": decimals,
+                    "mintAuthority": mint_authority.to_string(),
+                    "freezeAuthority": Option::<Pubkey>::from(freeze_authority).map(|key| key.to_string()),
+                }),
+            ),
+            TokenInstruction::MintTo { amount } => (
+                program,
+                "mintTo",
+                json!({ "mint": account(0), "account": account(1), "mintAuthority": account(2), "amount": amount }),
+            ),
+            TokenInstruction::Transfer { amount } => (
+                program,
+                "transfer",
+                json!({ "source": account(0), "destination": account(1), "authority": account(2), "amount": amount }),
+            ),
+            _ => return None,
+        }
+    } else if *program_id == solana_sdk::ed25519_program::id() || *program_id == solana_sdk::secp256k1_program::id() {
+        let program = if *program_id == solana_sdk::ed25519_program::id() { "ed25519" } else { "secp256k1" };
+        // Both precompiles start with the signature count
+        let signatures = *instruction.data.first()?;
+        (program, "verify", json!({ "signatures": signatures }))
+    } else {
+        return None;
+    };
+
+    Some(DecodedInstruction {
+        program: program.to_string(),
+        instruction_type: instruction_type.to_string(),
+        info,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use solana_sdk::system_instruction;
+
+    fn options(data_encoding: DataEncoding, decoded: bool) -> InstructionOptions {
+        InstructionOptions { data_encoding, decoded }
+    }
+
+    fn decoded(instruction: &Instruction) -> Value {
+        serde_json::to_value(decode(instruction).expect("recognised instruction")).unwrap()
+    }
+
+    #[test]
+    fn data_is_encoded_as_asked() {
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[0, 1, 254, 255], vec![]);
+        let data = |encoding| ApiInstruction::new(&instruction, options(encoding, false)).instruction_data;
+        assert_eq!(data(DataEncoding::Base64), "AAH+/w==");
+        assert_eq!(data(DataEncoding::Hex), "0001feff");
+        assert_eq!(data(DataEncoding::Base58), bs58::encode([0, 1, 254, 255]).into_string());
+    }
+
+    #[test]
+    fn decoded_view_is_opt_in() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let instruction = system_instruction::transfer(&from, &to, 42);
+        assert!(ApiInstruction::new(&instruction, InstructionOptions::default()).decoded.is_none());
+
+        let api = ApiInstruction::new(&instruction, options(DataEncoding::Base64, true));
+        assert_eq!(
+            serde_json::to_value(api.decoded).unwrap(),
+            serde_json::json!({
+                "program": "system",
+                "type": "transfer",
+                "info": { "source": from.to_string(), "destination": to.to_string(), "lamports": 42 },
+            })
+        );
+    }
+
+    #[test]
+    fn token_instructions_are_decoded_for_both_token_programs() {
+        let (mint, account, authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let token_2022: Pubkey = TOKEN_2022_PROGRAM_ID.parse().unwrap();
+
+        for (program_id, program) in [(spl_token::id(), "spl-token"), (token_2022, "spl-token-2022")] {
+            // spl_token only builds for its own program id; the layout is shared
+            let retarget = |mut instruction: Instruction| {
+                instruction.program_id = program_id;
+                instruction
+            };
+
+            let initialize = retarget(
+                spl_token::instruction::initialize_mint(&spl_token::id(), &mint, &authority, None, 6).unwrap(),
+            );
+            let view = decoded(&initialize);
+            assert_eq!(view["program"], program);
+            assert_eq!(view["type"], "initializeMint");
+            assert_eq!(view["info"]["mint"], mint.to_string());
+            assert_eq!(view["info"]["decimals"], 6);
+            assert_eq!(view["info"]["mintAuthority"], authority.to_string());
+            assert_eq!(view["info"]["freezeAuthority"], Value::Null);
+
+            let mint_to = retarget(
+                spl_token::instruction::mint_to(&spl_token::id(), &mint, &account, &authority, &[], 7).unwrap(),
+            );
+            assert_eq!(
+                decoded(&mint_to),
+                serde_json::json!({
+                    "program": program,
+                    "type": "mintTo",
+                    "info": {
+                        "mint": mint.to_string(),
+                        "account": account.to_string(),
+                        "mintAuthority": authority.to_string(),
+                        "amount": 7,
+                    },
+                })
+            );
+
+            let transfer = retarget(
+                spl_token::instruction::transfer(&spl_token::id(), &account, &mint, &authority, &[], 9).unwrap(),
+            );
+            let view = decoded(&transfer);
+            assert_eq!(view["type"], "transfer");
+            assert_eq!(view["info"]["source"], account.to_string());
+            assert_eq!(view["info"]["destination"], mint.to_string());
+            assert_eq!(view["info"]["amount"], 9);
+        }
+    }
+
+    #[test]
+    fn unknown_data_is_not_decoded() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let unknown_program = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let garbage = Instruction::new_with_bytes(spl_token::id(), &[255, 1], vec![]);
+        let other_system = system_instruction::assign(&from, &to);
+        let other_token = spl_token::instruction::sync_native(&spl_token::id(), &from).unwrap();
+
+        for instruction in [unknown_program, garbage, other_system, other_token] {
+            assert!(decode(&instruction).is_none(), "{:?}", instruction);
+            assert!(ApiInstruction::new(&instruction, options(DataEncoding::Hex, true)).decoded.is_none());
+        }
+    }
+
+    #[test]
+    fn v1_refuses_options_it_cannot_honour() {
+        assert!(InstructionOptions::default().check(ApiVersion::V1).is_ok());
+        for (options, field) in [
+            (options(DataEncoding::Hex, false), "dataEncoding"),
+            (options(DataEncoding::Base58, false), "dataEncoding"),
+            (options(DataEncoding::Base64, true), "decoded"),
+        ] {
+            let error = options.check(ApiVersion::V1).unwrap_err();
+            assert_eq!(error.field.as_deref(), Some(field));
+            assert!(options.check(ApiVersion::V2).is_ok());
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod instruction;
 pub mod requests;
 pub mod responses; 
+pub mod rpc;
+pub mod v2;
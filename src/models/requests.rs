@@ -1,45 +1,204 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::instruction::InstructionOptions;
+use crate::utils::encoding::{MessageEncoding, SignatureEncoding};
+use crate::utils::keystore::Keystore;
+use crate::utils::offchain::SigningFormat;
+use crate::utils::redact::Redacted;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct KeypairRequest {
+    /// When set, the secret is returned as an encrypted keystore instead of raw base58
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub password: Option<Redacted<String>>,
+    /// When true, the key is kept in the server-side vault and only its key id is returned.
+    /// Cannot be combined with `password`.
+    #[serde(default)]
+    pub store: bool,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DecryptKeystoreRequest {
+    pub keystore: Keystore,
+    #[schema(value_type = String)]
+    pub password: Redacted<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct CreateTokenRequest {
     #[serde(alias = "mintAuthority")]
     pub payer: String,
     pub mint: String,
     pub decimals: u8,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct MintTokenRequest {
     pub mint: String,
     pub destination: String,
     pub authority: String,
     pub amount: u64,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SplitKeypairRequest {
+    #[schema(value_type = String)]
+    pub secret: Redacted<String>,
+    pub shares: u8,
+    pub threshold: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct CombineSharesRequest {
+    #[schema(value_type = Vec<String>)]
+    pub shares: Vec<Redacted<String>>,
+    pub pubkey: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SignMessageRequest {
     pub message: String,
-    pub secret: String,
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub secret: Redacted<String>,
+    /// Alternative to `secret`: a key held in the server-side vault
+    #[serde(default, rename = "keyId", alias = "key_id")]
+    pub key_id: Option<String>,
+    /// Alternative to `secret`: an encrypted keystore plus its password
+    #[serde(default)]
+    pub keystore: Option<Keystore>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub password: Option<Redacted<String>>,
+    #[serde(default)]
+    pub format: SigningFormat,
+    /// How `message` is encoded; defaults to UTF-8 text
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    /// Encoding of the returned signature; defaults to base64
+    #[serde(default, rename = "signatureEncoding", alias = "signature_encoding")]
+    pub signature_encoding: SignatureEncoding,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct VerifyMessageRequest {
     pub message: String,
     pub signature: String,
     pub pubkey: String,
+    #[serde(default)]
+    pub format: SigningFormat,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    #[serde(default, rename = "signatureEncoding", alias = "signature_encoding")]
+    pub signature_encoding: SignatureEncoding,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SendSolRequest {
     pub from: String,
     pub to: String,
     pub lamports: u64,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SendTokenRequest {
     pub destination: String,
     pub mint: String,
     pub owner: String,
     pub amount: u64,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
 } 
+
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SiwsChallengeRequest {
+    pub domain: String,
+    pub address: String,
+    #[serde(default)]
+    pub statement: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default, rename = "chainId", alias = "chain_id")]
+    pub chain_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SiwsVerifyRequest {
+    pub message: String,
+    pub signature: String,
+    #[serde(default, rename = "signatureEncoding", alias = "signature_encoding")]
+    pub signature_encoding: SignatureEncoding,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SiwsSessionRequest {
+    /// A token returned by `/auth/siws/verify`.
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct Ed25519SignatureEntry {
+    pub message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    /// With `signature`: an existing signature to embed. Omit when signing with `secret` or `keyId`.
+    #[serde(default)]
+    pub pubkey: String,
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default, rename = "signatureEncoding", alias = "signature_encoding")]
+    pub signature_encoding: SignatureEncoding,
+    /// Key to sign with instead of supplying `pubkey` and `signature`.
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub secret: Redacted<String>,
+    #[serde(default, rename = "keyId", alias = "key_id")]
+    pub key_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct Ed25519InstructionRequest {
+    pub signatures: Vec<Ed25519SignatureEntry>,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct Secp256k1SignatureEntry {
+    pub message: String,
+    #[serde(default)]
+    pub encoding: MessageEncoding,
+    /// With `signature` and `recoveryId`: an existing signature to embed. Omit when signing with `secret`.
+    #[serde(default, rename = "ethAddress", alias = "eth_address")]
+    pub eth_address: String,
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default, rename = "signatureEncoding", alias = "signature_encoding")]
+    pub signature_encoding: SignatureEncoding,
+    #[serde(default, rename = "recoveryId", alias = "recovery_id")]
+    pub recovery_id: Option<u8>,
+    /// Hex secp256k1 private key to sign with instead of supplying an existing signature.
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub secret: Redacted<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct Secp256k1InstructionRequest {
+    pub signatures: Vec<Secp256k1SignatureEntry>,
+    /// Position of the instruction in the transaction; the offsets reference it
+    #[serde(default, rename = "instructionIndex", alias = "instruction_index")]
+    pub instruction_index: u8,
+    #[serde(flatten)]
+    pub output: InstructionOptions,
+}
\ No newline at end of file
@@ -1,12 +1,23 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::error::{ApiError, ErrorCode, ParseErrorDetails};
+use crate::utils::keystore::Keystore;
+use crate::vault::KeyRecord;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ApiResponse<T> {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    // Request field the error refers to, as a serde path (e.g. `signatures[0].pubkey`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<ParseErrorDetails>,
 }
 
 impl<T> ApiResponse<T> {
@@ -15,6 +26,9 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
+            field: None,
+            details: None,
         }
     }
 
@@ -23,68 +37,199 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(error),
+            code: None,
+            field: None,
+            details: None,
+        }
+    }
+
+    pub fn from_error(error: ApiError) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(error.message),
+            code: Some(error.code),
+            field: error.field,
+            details: error.details,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Response in the shape of the API version the request was routed under; see
+/// `routes::ApiVersion`.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum Versioned<V1, V2> {
+    V1(V1),
+    V2(V2),
+}
+
+/// Per-item outcome in a batch call; failures are reported here instead of failing the call.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct BatchItem<T> {
+    pub index: usize,
+    #[serde(flatten)]
+    pub result: ApiResponse<T>,
+}
+
+impl<T> BatchItem<T> {
+    pub fn new(index: usize, result: Result<T, ApiError>) -> Self {
+        let result = match result {
+            Ok(data) => ApiResponse::success(data),
+            Err(e) => ApiResponse::from_error(e),
+        };
+        Self { index, result }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct BatchResponse<T> {
+    pub results: Vec<BatchItem<T>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct KeypairResponse {
     pub pubkey: String,
-    pub secret: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<Keystore>,
+    #[serde(rename = "keyId", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SplitKeypairResponse {
+    pub pubkey: String,
+    pub threshold: u8,
+    pub shares: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct KeyListResponse {
+    pub keys: Vec<KeyRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct KeyDeleteResponse {
+    #[serde(rename = "keyId")]
+    pub key_id: String,
+    pub deleted: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct AccountMeta {
     pub pubkey: String,
     pub is_signer: bool,
     pub is_writable: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct InstructionResponse {
     pub program_id: String,
     pub accounts: Vec<AccountMeta>,
     pub instruction_data: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SignatureResponse {
     pub signature: String,
     pub public_key: String,
     pub message: String,
+    // Off-chain message format (restrictedAscii / limitedUtf8 / extendedUtf8), when used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_format: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct VerificationResponse {
     pub valid: bool,
     pub message: String,
     pub pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_format: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SolTransferResponse {
     pub program_id: String,
     pub accounts: Vec<String>,
     pub instruction_data: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct TokenAccountMeta {
     pub pubkey: String,
     #[serde(rename = "isSigner")]
     pub is_signer: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct TokenTransferResponse {
     pub program_id: String,
     pub accounts: Vec<TokenAccountMeta>,
     pub instruction_data: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct TokenCreateResponse {
     pub program_id: String,
     pub accounts: AccountMeta, // Single account object as per spec
     pub instruction_data: String,
 } 
+
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SiwsChallengeResponse {
+    pub message: String,
+    pub nonce: String,
+    #[serde(rename = "issuedAt")]
+    pub issued_at: String,
+    #[serde(rename = "expirationTime")]
+    pub expiration_time: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SiwsSessionResponse {
+    pub token: String,
+    pub address: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: String,
+}
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub checks: Vec<DependencyCheck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct VersionResponse {
+    pub version: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: String,
+    /// Endpoint groups switched on in `[features]`.
+    pub features: Vec<String>,
+    #[serde(rename = "solanaSdk")]
+    pub solana_sdk: String,
+    #[serde(rename = "splToken")]
+    pub spl_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RentResponse {
+    pub space: u64,
+    pub lamports: u64,
+}
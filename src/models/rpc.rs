@@ -0,0 +1,84 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use utoipa::ToSchema;
+use crate::error::{ErrorCode, ParseErrorDetails};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+// Start of the range JSON-RPC reserves for implementation-defined server errors
+pub const SERVER_ERROR: i64 = -32000;
+
+/// A JSON-RPC 2.0 call. Without `id` it is a notification: it runs, but gets no response.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RpcRequest {
+    /// Always `"2.0"`.
+    pub jsonrpc: String,
+    /// Operation name, e.g. `sendSol`.
+    pub method: String,
+    /// The REST request body, as an object (or an array for the batch operations). Path
+    /// parameters such as `keyId` and `space` are passed here too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    pub params: Option<Value>,
+    /// String, number or null.
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    pub id: Option<Value>,
+}
+
+// Tells `"id": null` (a call) apart from a missing id (a notification)
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    /// The `data` of the REST response envelope, exactly as the endpoint returns it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    pub result: Option<Box<RawValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    /// The id of the call, or null when it could not be read.
+    #[schema(value_type = Object)]
+    pub id: Value,
+}
+
+impl RpcResponse {
+    pub fn result(id: Value, result: Box<RawValue>) -> Self {
+        Self { jsonrpc: "2.0".to_string(), result: Some(result), error: None, id }
+    }
+
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self { jsonrpc: "2.0".to_string(), result: None, error: Some(error), id }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<RpcErrorData>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+}
+
+/// The error fields of the REST response envelope.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RpcErrorData {
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<ParseErrorDetails>,
+}
@@ -0,0 +1,87 @@
+//! Response shapes served under `/v2`, where fields are camelCase throughout.
+//! Instruction builders answer with `models::instruction::ApiInstruction`. Keystores keep
+//! their file format in both versions, since they are fed back into `/keypair/decrypt`.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::responses as v1;
+use crate::vault;
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v2::SignatureResponse)]
+pub struct SignatureResponse {
+    pub signature: String,
+    pub public_key: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_format: Option<String>,
+}
+
+impl From<v1::SignatureResponse> for SignatureResponse {
+    fn from(response: v1::SignatureResponse) -> Self {
+        Self {
+            signature: response.signature,
+            public_key: response.public_key,
+            message: response.message,
+            message_format: response.message_format,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v2::VerificationResponse)]
+pub struct VerificationResponse {
+    pub valid: bool,
+    pub message: String,
+    pub pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_format: Option<String>,
+}
+
+impl From<v1::VerificationResponse> for VerificationResponse {
+    fn from(response: v1::VerificationResponse) -> Self {
+        Self {
+            valid: response.valid,
+            message: response.message,
+            pubkey: response.pubkey,
+            message_format: response.message_format,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v2::KeyRecord)]
+pub struct KeyRecord {
+    pub key_id: String,
+    pub pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub disabled: bool,
+    pub created_at: u64,
+}
+
+impl From<vault::KeyRecord> for KeyRecord {
+    fn from(record: vault::KeyRecord) -> Self {
+        Self {
+            key_id: record.key_id,
+            pubkey: record.pubkey,
+            label: record.label,
+            disabled: record.disabled,
+            created_at: record.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[schema(as = v2::KeyListResponse)]
+pub struct KeyListResponse {
+    pub keys: Vec<KeyRecord>,
+}
+
+impl From<v1::KeyListResponse> for KeyListResponse {
+    fn from(response: v1::KeyListResponse) -> Self {
+        Self { keys: response.keys.into_iter().map(KeyRecord::from).collect() }
+    }
+}
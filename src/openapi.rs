@@ -0,0 +1,295 @@
+use utoipa::openapi::schema::{Object, Schema};
+use utoipa::openapi::path::Operation;
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::openapi::{OpenApi as OpenApiSpec, RefOr};
+use utoipa::{Modify, OpenApi};
+use crate::handlers::{auth, health, keypair, keys, message, precompile, rent, rpc, token, transfer};
+use axum::http::Method;
+use crate::models::instruction::ApiInstruction;
+use crate::models::responses::ApiResponse;
+use crate::models::v2;
+use crate::routes;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Solana HTTP Server",
+        description = "Keypair, message signing, SPL token and transfer instruction endpoints. \
+            Errors are returned with HTTP 200 and `success: false` unless the request sends \
+            `X-Error-Mode: http`.\n\n\
+            Every path is also served under `/v1` (identical) and `/v2`. Under `/v2` response \
+            fields are camelCase and instruction builders return the `Instruction` schema, which \
+            always lists `accounts` as an array of `AccountMeta`; the affected operations name \
+            their `v2` schema.",
+    ),
+    paths(
+        keypair::generate_keypair,
+        keypair::decrypt_keystore,
+        keypair::split_keypair,
+        keypair::combine_shares,
+        keys::list_keys,
+        keys::get_key,
+        keys::delete_key,
+        keys::disable_key,
+        keys::enable_key,
+        message::sign_message,
+        message::verify_message,
+        message::sign_batch,
+        message::verify_batch,
+        auth::siws_challenge,
+        auth::siws_verify,
+        auth::siws_session,
+        token::create_token,
+        token::mint_token,
+        precompile::build_ed25519_instruction,
+        precompile::build_secp256k1_instruction,
+        transfer::send_sol,
+        transfer::send_token,
+        rent::rent_exempt_minimum,
+        rpc::handle_rpc,
+        health::healthz,
+        health::readyz,
+        health::version,
+    ),
+    // Error bodies are only referenced through `ApiErrorResponses`
+    components(schemas(
+        ApiResponse<serde_json::Value>,
+        ApiInstruction,
+        v2::SignatureResponse,
+        v2::VerificationResponse,
+        v2::KeyListResponse,
+    )),
+    modifiers(&SerdeAliases, &ApiKeySecurity, &RouteScopes),
+    security(("api_key" = []), ("bearer" = [])),
+    tags(
+        (name = "keypair", description = "Key generation, keystores and Shamir shares"),
+        (name = "keys", description = "Server-side key vault"),
+        (name = "message", description = "Message signing and verification"),
+        (name = "auth", description = "Sign-In With Solana"),
+        (name = "token", description = "SPL token instructions"),
+        (name = "precompile", description = "Ed25519 and Secp256k1 program instructions"),
+        (name = "transfer", description = "SOL and SPL token transfer instructions"),
+        (name = "rent", description = "Rent-exempt minimum balances"),
+        (name = "rpc", description = "JSON-RPC 2.0 access to every operation"),
+        (name = "operations", description = "Health checks and build information"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// Documents the two ways of presenting an API key; see `middleware::api_key`.
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// Sets each operation's security from the route registry: public endpoints need no
+/// key, scoped ones list the scope a key must grant.
+struct RouteScopes;
+
+impl Modify for RouteScopes {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        for endpoint in routes::endpoints().iter().filter(|endpoint| endpoint.documented) {
+            let operation = openapi
+                .paths
+                .paths
+                .get_mut(&openapi_path(endpoint.path))
+                .and_then(|item| operation_mut(item, &endpoint.method));
+            let Some(operation) = operation else {
+                debug_assert!(false, "{} {} is not in the OpenAPI paths", endpoint.method, endpoint.path);
+                continue;
+            };
+
+            match endpoint.scope {
+                None => operation.security = Some(vec![SecurityRequirement::default()]),
+                Some(scope) => {
+                    let note = format!("Requires an API key with the `{}` scope.", scope.as_str());
+                    operation.description = Some(match operation.description.take() {
+                        Some(description) => format!("{}\n\n{}", description, note),
+                        None => note,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// `/keys/:key_id` -> `/keys/{key_id}`
+fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(param) => format!("{{{}}}", param),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn operation_mut<'a>(item: &'a mut utoipa::openapi::PathItem, method: &Method) -> Option<&'a mut Operation> {
+    match *method {
+        Method::GET => item.get.as_mut(),
+        Method::POST => item.post.as_mut(),
+        Method::PUT => item.put.as_mut(),
+        Method::DELETE => item.delete.as_mut(),
+        Method::PATCH => item.patch.as_mut(),
+        _ => None,
+    }
+}
+
+// Input-only names accepted through `#[serde(alias)]`, which the schema derive cannot see:
+// (schema, documented property, alias)
+const ALIASES: &[(&str, &str, &str)] = &[
+    ("CreateTokenRequest", "payer", "mintAuthority"),
+    ("SignMessageRequest", "keyId", "key_id"),
+    ("SignMessageRequest", "signatureEncoding", "signature_encoding"),
+    ("VerifyMessageRequest", "signatureEncoding", "signature_encoding"),
+    ("SiwsChallengeRequest", "chainId", "chain_id"),
+    ("SiwsVerifyRequest", "signatureEncoding", "signature_encoding"),
+    ("Ed25519SignatureEntry", "signatureEncoding", "signature_encoding"),
+    ("Ed25519SignatureEntry", "keyId", "key_id"),
+    ("Secp256k1SignatureEntry", "ethAddress", "eth_address"),
+    ("Secp256k1SignatureEntry", "signatureEncoding", "signature_encoding"),
+    ("Secp256k1SignatureEntry", "recoveryId", "recovery_id"),
+    ("Secp256k1InstructionRequest", "instructionIndex", "instruction_index"),
+    ("InstructionOptions", "dataEncoding", "data_encoding"),
+];
+
+/// Adds each serde alias as a property of its own, so generated clients can use either name.
+struct SerdeAliases;
+
+impl Modify for SerdeAliases {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+
+        for (schema, property, alias) in ALIASES {
+            match components.schemas.get_mut(*schema) {
+                Some(RefOr::T(Schema::Object(object))) => add_alias(object, property, alias),
+                // Requests with `#[serde(flatten)]` fields are an `allOf` of inline objects
+                Some(RefOr::T(Schema::AllOf(all_of))) => {
+                    for item in &mut all_of.items {
+                        if let RefOr::T(Schema::Object(object)) = item {
+                            add_alias(object, property, alias);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn add_alias(object: &mut Object, property: &str, alias: &str) {
+    let Some(original) = object.properties.get(property).cloned() else {
+        return;
+    };
+
+    let mut aliased = original;
+    set_description(&mut aliased, format!("Alias of `{}`.", property));
+    object.properties.insert(alias.to_string(), aliased);
+
+    // Either name satisfies a required property, so neither can be listed as required
+    if let Some(index) = object.required.iter().position(|name| name == property) {
+        object.required.remove(index);
+        if let Some(schema) = object.properties.get_mut(property) {
+            set_description(schema, format!("Required; may be sent as `{}` instead.", alias));
+        }
+    }
+}
+
+fn set_description(schema: &mut RefOr<Schema>, description: String) {
+    match schema {
+        RefOr::Ref(reference) => reference.description = description,
+        RefOr::T(Schema::Object(object)) => object.description = Some(description),
+        RefOr::T(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_documented_route_is_in_the_spec() {
+        let spec = ApiDoc::openapi();
+        let endpoints = routes::endpoints();
+        for endpoint in endpoints.iter().filter(|endpoint| endpoint.documented) {
+            let mut item = spec.paths.paths.get(&openapi_path(endpoint.path)).cloned();
+            let operation = item.as_mut().and_then(|item| operation_mut(item, &endpoint.method));
+            assert!(operation.is_some(), "{} {} is not in the OpenAPI spec", endpoint.method, endpoint.path);
+        }
+
+        // ...and the spec describes no route the server does not serve
+        for path in spec.paths.paths.keys() {
+            assert!(
+                endpoints.iter().any(|endpoint| endpoint.documented && openapi_path(endpoint.path) == *path),
+                "{} is documented but not routed",
+                path
+            );
+        }
+    }
+
+    // (struct, documented property, alias) for every `alias = "..."` in the request models
+    fn declared_aliases() -> Vec<(String, String, String)> {
+        let quoted = |line: &str, key: &str| {
+            let rest = &line[line.find(key)? + key.len()..];
+            rest.split('"').next().map(str::to_string)
+        };
+
+        let mut aliases = Vec::new();
+        for source in [include_str!("models/requests.rs"), include_str!("models/instruction.rs")] {
+            let lines: Vec<&str> = source.lines().map(str::trim).collect();
+            let mut schema = String::new();
+            for (index, line) in lines.iter().enumerate() {
+                if let Some(name) = line.strip_prefix("pub struct ") {
+                    schema = name.split([' ', '{', '<']).next().unwrap_or_default().to_string();
+                }
+                let Some(alias) = quoted(line, "alias = \"") else {
+                    continue;
+                };
+                let property = quoted(line, "rename = \"").unwrap_or_else(|| {
+                    let field = lines[index..].iter().find_map(|line| line.strip_prefix("pub ")).unwrap();
+                    field.split(':').next().unwrap().to_string()
+                });
+                aliases.push((schema.clone(), property, alias));
+            }
+        }
+        aliases
+    }
+
+    #[test]
+    fn aliases_match_the_request_models() {
+        let mut declared = declared_aliases();
+        let mut listed: Vec<_> = ALIASES
+            .iter()
+            .map(|(schema, property, alias)| (schema.to_string(), property.to_string(), alias.to_string()))
+            .collect();
+        declared.sort();
+        listed.sort();
+        assert_eq!(listed, declared, "ALIASES is out of date with the `#[serde(alias)]` attributes");
+
+        // Every alias ends up in the generated schema
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for (name, _, alias) in ALIASES {
+            let schema = &spec["components"]["schemas"][*name];
+            let found = std::iter::once(schema)
+                .chain(schema["allOf"].as_array().into_iter().flatten())
+                .any(|object| object["properties"].get(*alias).is_some());
+            assert!(found, "{} has no `{}` property", name, alias);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use async_trait::async_trait;
+use super::{take_token, Decision, Limit, RateLimitError, RateLimitStore};
+
+// Above this many buckets, idle ones are dropped before a new one is added
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_ms: u64,
+    period_ms: u64,
+}
+
+/// Per-process token buckets; limits are not shared between server instances.
+pub struct MemoryRateLimitStore {
+    started: Instant,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Synchronous variant of `acquire`, for callers outside an async context.
+    pub fn acquire_now(&self, key: &str, limit: Limit) -> Decision {
+        let now_ms = self.started.elapsed().as_millis() as u64;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !buckets.contains_key(key) && buckets.len() >= PRUNE_THRESHOLD {
+            // A bucket idle for a whole period is full again and can be recreated on demand
+            buckets.retain(|_, b| now_ms.saturating_sub(b.updated_ms) < b.period_ms);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert_with(|| Bucket {
+            tokens: limit.capacity as f64,
+            updated_ms: now_ms,
+            period_ms: limit.period.as_millis() as u64,
+        });
+        take_token(&mut bucket.tokens, &mut bucket.updated_ms, now_ms, limit)
+    }
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<Decision, RateLimitError> {
+        Ok(self.acquire_now(key, limit))
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+pub mod memory;
+pub mod redis;
+pub mod resp;
+
+pub use memory::MemoryRateLimitStore;
+pub use redis::RedisRateLimitStore;
+
+/// Token bucket parameters: up to `capacity` requests in a burst, refilled at
+/// `capacity` tokens per `period`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limit {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl Limit {
+    /// Parses `<count>/<unit>` where the unit is `s`, `m` or `h`, e.g. `60/m`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid rate limit: {} (expected e.g. 60/m)", spec);
+
+        let (count, unit) = spec.split_once('/').ok_or_else(invalid)?;
+        let capacity: u32 = count.trim().parse().map_err(|_| invalid())?;
+        let period = match unit.trim() {
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3600),
+            _ => return Err(invalid()),
+        };
+        if capacity == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Limit { capacity, period })
+    }
+
+    /// Tokens added back per millisecond.
+    pub fn refill_per_ms(&self) -> f64 {
+        self.capacity as f64 / self.period.as_millis() as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub remaining: u32,
+    /// How long until the request would be allowed; zero when it was.
+    pub retry_after: Duration,
+}
+
+#[derive(Debug)]
+pub struct RateLimitError(pub String);
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate limit store error: {}", self.0)
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+/// Holds the token buckets. Implementations must update a bucket atomically so that
+/// several server instances can share one store.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<Decision, RateLimitError>;
+}
+
+/// Buckets are split by endpoint class so expensive calls cannot starve cheap ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointClass {
+    Default,
+    /// scrypt key derivation and batch endpoints.
+    Expensive,
+}
+
+// Routes whose cost depends on the body: signing with a `keystore` runs scrypt
+const KEYSTORE_ROUTES: &[&str] = &["/message/sign"];
+
+impl EndpointClass {
+    pub fn for_path(path: &str) -> Self {
+        match path {
+            "/keypair" | "/keypair/decrypt" | "/message/sign-batch" | "/message/verify-batch" => {
+                EndpointClass::Expensive
+            }
+            _ => EndpointClass::Default,
+        }
+    }
+
+    /// Whether `for_request` needs the body of requests to `path`.
+    pub fn depends_on_body(path: &str) -> bool {
+        KEYSTORE_ROUTES.contains(&path)
+    }
+
+    /// Like `for_path`, but also counts single signs that decrypt a keystore as expensive.
+    pub fn for_request(path: &str, body: &[u8]) -> Self {
+        #[derive(Deserialize)]
+        struct Probe {
+            keystore: Option<IgnoredAny>,
+        }
+
+        let decrypts_keystore = Self::depends_on_body(path)
+            && serde_json::from_slice::<Probe>(body).is_ok_and(|probe| probe.keystore.is_some());
+        if decrypts_keystore {
+            EndpointClass::Expensive
+        } else {
+            Self::for_path(path)
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EndpointClass::Default => "default",
+            EndpointClass::Expensive => "expensive",
+        }
+    }
+}
+
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    default: Limit,
+    expensive: Limit,
+    trust_forwarded_for: bool,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>, default: Limit, expensive: Limit) -> Self {
+        Self {
+            store,
+            default,
+            expensive,
+            trust_forwarded_for: false,
+        }
+    }
+
+    /// Keys anonymous clients by the first `X-Forwarded-For` address instead of the peer.
+    /// Only enable behind a proxy that overwrites the header.
+    pub fn with_trust_forwarded_for(mut self, trust: bool) -> Self {
+        self.trust_forwarded_for = trust;
+        self
+    }
+
+    pub fn trust_forwarded_for(&self) -> bool {
+        self.trust_forwarded_for
+    }
+
+    /// Takes one token from `client`'s bucket for the given endpoint class.
+    pub async fn check(&self, client: &str, class: EndpointClass) -> Result<Decision, RateLimitError> {
+        let limit = match class {
+            EndpointClass::Default => self.default,
+            EndpointClass::Expensive => self.expensive,
+        };
+        let key = format!("ratelimit:{}:{}", class.as_str(), client);
+        self.store.acquire(&key, limit).await
+    }
+}
+
+/// Refills `tokens` for the time since `updated_ms` and takes one token if available.
+/// Shared by the in-memory store and mirrored by the Redis script.
+pub(crate) fn take_token(tokens: &mut f64, updated_ms: &mut u64, now_ms: u64, limit: Limit) -> Decision {
+    let elapsed = now_ms.saturating_sub(*updated_ms) as f64;
+    *tokens = (*tokens + elapsed * limit.refill_per_ms()).min(limit.capacity as f64);
+    *updated_ms = now_ms;
+
+    if *tokens >= 1.0 {
+        *tokens -= 1.0;
+        return Decision {
+            allowed: true,
+            remaining: *tokens as u32,
+            retry_after: Duration::ZERO,
+        };
+    }
+
+    let wait_ms = ((1.0 - *tokens) / limit.refill_per_ms()).ceil() as u64;
+    Decision {
+        allowed: false,
+        remaining: 0,
+        retry_after: Duration::from_millis(wait_ms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_a_burst_then_refills() {
+        let limit = Limit::parse("3/s").unwrap();
+        let (mut tokens, mut updated) = (limit.capacity as f64, 0);
+
+        let remaining: Vec<u32> = (0..3).map(|_| take_token(&mut tokens, &mut updated, 0, limit).remaining).collect();
+        assert_eq!(remaining, [2, 1, 0]);
+
+        let denied = take_token(&mut tokens, &mut updated, 0, limit);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Duration::from_millis(334));
+
+        // One token every 333⅓ ms, never more than the capacity
+        assert!(!take_token(&mut tokens, &mut updated, 300, limit).allowed);
+        assert!(take_token(&mut tokens, &mut updated, 340, limit).allowed);
+        assert_eq!(take_token(&mut tokens, &mut updated, 60_000, limit).remaining, 2);
+    }
+
+    #[test]
+    fn keystore_signs_are_expensive() {
+        let keystore = br#"{"message": "hi", "keystore": {"version": 1}, "password": "pw"}"#;
+        let secret = br#"{"message": "hi", "secret": "..."}"#;
+
+        assert_eq!(EndpointClass::for_request("/message/sign", keystore), EndpointClass::Expensive);
+        assert_eq!(EndpointClass::for_request("/message/sign", secret), EndpointClass::Default);
+        assert_eq!(EndpointClass::for_request("/message/sign", b"not json"), EndpointClass::Default);
+        assert_eq!(EndpointClass::for_request("/keypair/decrypt", b""), EndpointClass::Expensive);
+        assert_eq!(EndpointClass::for_request("/message/verify", keystore), EndpointClass::Default);
+    }
+
+    #[test]
+    fn limits_parse() {
+        assert_eq!(Limit::parse("60/m").unwrap(), Limit { capacity: 60, period: Duration::from_secs(60) });
+        for spec in ["0/s", "10", "10/d", "-1/s"] {
+            assert!(Limit::parse(spec).is_err(), "{}", spec);
+        }
+    }
+}
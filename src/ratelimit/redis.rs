@@ -0,0 +1,210 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::{timeout_at, Instant};
+use super::resp::{self, Value};
+use super::{Decision, Limit, RateLimitError, RateLimitStore};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Token bucket update run atomically on the server, using the server clock so that
+/// instances with skewed clocks agree. Mirrors `take_token`.
+///
+/// KEYS[1] = bucket, ARGV = capacity, refill per ms. Returns {allowed, remaining, retry ms}.
+pub const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or capacity
+local updated = tonumber(state[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * rate)
+local allowed, retry = 0, 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  retry = math.ceil((1 - tokens) / rate)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / rate))
+return {allowed, math.floor(tokens), retry}
+"#;
+
+/// Shares buckets between server instances through Redis (or anything speaking RESP
+/// with `EVAL`). A single connection is kept open and re-established after errors.
+pub struct RedisRateLimitStore {
+    address: String,
+    password: Option<String>,
+    timeout: Duration,
+    connection: Mutex<Option<BufReader<TcpStream>>>,
+}
+
+impl RedisRateLimitStore {
+    /// `url` is `redis://[:password@]host:port`.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("redis://")
+            .ok_or_else(|| format!("Unsupported rate limit store URL: {}", url))?;
+        let rest = rest.trim_end_matches('/');
+
+        let (password, address) = match rest.rsplit_once('@') {
+            Some((auth, address)) => (Some(auth.trim_start_matches(':').to_string()), address),
+            None => (None, rest),
+        };
+        if address.is_empty() {
+            return Err(format!("Unsupported rate limit store URL: {}", url));
+        }
+
+        Ok(Self {
+            address: address.to_string(),
+            password: password.filter(|p| !p.is_empty()),
+            timeout: DEFAULT_TIMEOUT,
+            connection: Mutex::new(None),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn connect(&self) -> Result<BufReader<TcpStream>, RateLimitError> {
+        let stream = TcpStream::connect(&self.address).await.map_err(store_error)?;
+        let mut connection = BufReader::new(stream);
+
+        if let Some(password) = &self.password {
+            let reply = round_trip(&mut connection, &[b"AUTH", password.as_bytes()]).await?;
+            if let Value::Error(e) = reply {
+                return Err(RateLimitError(e));
+            }
+        }
+        Ok(connection)
+    }
+
+    /// Runs the token bucket script. `timeout` bounds the whole call, and a call cut
+    /// short mid-reply drops the connection so the next one does not read its leftovers.
+    async fn eval(&self, key: &str, limit: Limit) -> Result<Value, RateLimitError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut guard = timeout_at(deadline, self.connection.lock()).await.map_err(|_| timed_out())?;
+
+        let capacity = limit.capacity.to_string();
+        let rate = limit.refill_per_ms().to_string();
+        let args: [&[u8]; 6] = [
+            b"EVAL",
+            TOKEN_BUCKET_SCRIPT.as_bytes(),
+            b"1",
+            key.as_bytes(),
+            capacity.as_bytes(),
+            rate.as_bytes(),
+        ];
+
+        let result = timeout_at(deadline, async {
+            let connection = match guard.as_mut() {
+                Some(connection) => connection,
+                None => guard.insert(self.connect().await?),
+            };
+            round_trip(connection, &args).await
+        })
+        .await
+        .unwrap_or_else(|_| Err(timed_out()));
+
+        if result.is_err() {
+            // The stream may hold half a reply; start over on the next call
+            *guard = None;
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<Decision, RateLimitError> {
+        let reply = self.eval(key, limit).await?;
+
+        match reply {
+            Value::Array(Some(items)) => match items.as_slice() {
+                [Value::Integer(allowed), Value::Integer(remaining), Value::Integer(retry_ms)] => Ok(Decision {
+                    allowed: *allowed == 1,
+                    remaining: (*remaining).max(0) as u32,
+                    retry_after: Duration::from_millis((*retry_ms).max(0) as u64),
+                }),
+                _ => Err(RateLimitError("unexpected script reply".to_string())),
+            },
+            Value::Error(e) => Err(RateLimitError(e)),
+            _ => Err(RateLimitError("unexpected script reply".to_string())),
+        }
+    }
+}
+
+async fn round_trip(connection: &mut BufReader<TcpStream>, args: &[&[u8]]) -> Result<Value, RateLimitError> {
+    connection.get_mut().write_all(&resp::command(args)).await.map_err(store_error)?;
+    resp::read_value(connection).await.map_err(store_error)
+}
+
+fn store_error(e: std::io::Error) -> RateLimitError {
+    RateLimitError(e.to_string())
+}
+
+fn timed_out() -> RateLimitError {
+    RateLimitError("timed out".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    fn reply(allowed: i64) -> Vec<u8> {
+        let mut out = Vec::new();
+        Value::Array(Some(vec![Value::Integer(allowed), Value::Integer(0), Value::Integer(0)])).encode(&mut out);
+        out
+    }
+
+    #[tokio::test]
+    async fn timed_out_connection_is_not_reused() {
+        // The first connection answers "denied" too late; later ones answer "allowed" at once
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let first = accepted.fetch_add(1, Ordering::SeqCst) == 0;
+                tokio::spawn(async move {
+                    let mut connection = BufReader::new(stream);
+                    while resp::read_value(&mut connection).await.is_ok() {
+                        if first {
+                            tokio::time::sleep(Duration::from_millis(200)).await;
+                        }
+                        let _ = connection.get_mut().write_all(&reply(if first { 0 } else { 1 })).await;
+                    }
+                });
+            }
+        });
+
+        let store = RedisRateLimitStore::from_url(&format!("redis://{}", address))
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+        let limit = Limit::parse("1/s").unwrap();
+
+        assert_eq!(store.acquire("key", limit).await.unwrap_err().0, "timed out");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(store.acquire("key", limit).await.unwrap().allowed);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn urls_parse() {
+        let store = RedisRateLimitStore::from_url("redis://:secret@cache:6379/").unwrap();
+        assert_eq!((store.address.as_str(), store.password.as_deref()), ("cache:6379", Some("secret")));
+        assert!(RedisRateLimitStore::from_url("http://cache:6379").is_err());
+        assert!(RedisRateLimitStore::from_url("redis://").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Minimal RESP2 codec, enough to run scripts on a Redis-compatible server.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+// Replies here are tiny; anything larger means the peer is not speaking RESP
+const MAX_BULK_LEN: usize = 1 << 20;
+const MAX_ARRAY_LEN: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<Value>>),
+}
+
+impl Value {
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Simple(s) => out.extend_from_slice(format!("+{}\r\n", s).as_bytes()),
+            Value::Error(s) => out.extend_from_slice(format!("-{}\r\n", s).as_bytes()),
+            Value::Integer(i) => out.extend_from_slice(format!(":{}\r\n", i).as_bytes()),
+            Value::Bulk(None) => out.extend_from_slice(b"$-1\r\n"),
+            Value::Bulk(Some(bytes)) => {
+                out.extend_from_slice(format!("${}\r\n", bytes.len()).as_bytes());
+                out.extend_from_slice(bytes);
+                out.extend_from_slice(b"\r\n");
+            }
+            Value::Array(None) => out.extend_from_slice(b"*-1\r\n"),
+            Value::Array(Some(items)) => {
+                out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+                for item in items {
+                    item.encode(out);
+                }
+            }
+        }
+    }
+}
+
+/// Encodes a command as an array of bulk strings.
+pub fn command(args: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::new();
+    Value::Array(Some(args.iter().map(|a| Value::Bulk(Some(a.to_vec()))).collect())).encode(&mut out);
+    out
+}
+
+pub fn read_value<'a, R>(reader: &'a mut R) -> Pin<Box<dyn Future<Output = io::Result<Value>> + Send + 'a>>
+where
+    R: AsyncBufRead + Unpin + Send,
+{
+    Box::pin(async move {
+        let line = read_line(reader).await?;
+        let (kind, rest) = line.split_at(1);
+
+        match kind {
+            "+" => Ok(Value::Simple(rest.to_string())),
+            "-" => Ok(Value::Error(rest.to_string())),
+            ":" => Ok(Value::Integer(parse_int(rest)?)),
+            "$" => {
+                let len = parse_int(rest)?;
+                if len < 0 {
+                    return Ok(Value::Bulk(None));
+                }
+                let len = checked_len(len, MAX_BULK_LEN)?;
+                let mut bytes = vec![0u8; len + 2];
+                reader.read_exact(&mut bytes).await?;
+                bytes.truncate(len);
+                Ok(Value::Bulk(Some(bytes)))
+            }
+            "*" => {
+                let len = parse_int(rest)?;
+                if len < 0 {
+                    return Ok(Value::Array(None));
+                }
+                let len = checked_len(len, MAX_ARRAY_LEN)?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(read_value(reader).await?);
+                }
+                Ok(Value::Array(Some(items)))
+            }
+            _ => Err(protocol_error("unknown RESP type")),
+        }
+    })
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"));
+    }
+
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.is_empty() {
+        return Err(protocol_error("empty RESP line"));
+    }
+    Ok(line.to_string())
+}
+
+fn parse_int(value: &str) -> io::Result<i64> {
+    value.parse().map_err(|_| protocol_error("invalid RESP integer"))
+}
+
+fn checked_len(len: i64, max: usize) -> io::Result<usize> {
+    usize::try_from(len)
+        .ok()
+        .filter(|len| *len <= max)
+        .ok_or_else(|| protocol_error("RESP length out of range"))
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn decode(mut bytes: &[u8]) -> io::Result<Value> {
+        read_value(&mut bytes).await
+    }
+
+    #[tokio::test]
+    async fn values_round_trip() {
+        let value = Value::Array(Some(vec![
+            Value::Simple("OK".to_string()),
+            Value::Error("ERR nope".to_string()),
+            Value::Integer(-42),
+            Value::Bulk(Some(b"line\r\nbreak".to_vec())),
+            Value::Bulk(None),
+            Value::Array(None),
+            Value::Array(Some(vec![Value::Integer(1)])),
+        ]));
+        let mut out = Vec::new();
+        value.encode(&mut out);
+
+        assert_eq!(decode(&out).await.unwrap(), value);
+    }
+
+    #[test]
+    fn commands_are_arrays_of_bulk_strings() {
+        assert_eq!(command(&[b"GET", b"key"]), b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
+    }
+
+    #[tokio::test]
+    async fn malformed_replies_are_rejected() {
+        for reply in [&b"?1\r\n"[..], b":x\r\n", b"$2000000\r\n", b"\r\n", b"", b"$5\r\nab"] {
+            assert!(decode(reply).await.is_err(), "{:?}", String::from_utf8_lossy(reply));
+        }
+    }
+}
@@ -0,0 +1,788 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use axum::{
+    async_trait,
+    extract::{DefaultBodyLimit, FromRequestParts},
+    handler::Handler,
+    http::request::Parts,
+    http::{header, HeaderValue, Method, StatusCode},
+    response::IntoResponse,
+    routing::{self, MethodFilter, MethodRouter},
+    Extension, Router,
+};
+use axum::middleware::{from_fn, from_fn_with_state};
+use tower::ServiceBuilder;
+use tower_http::compression::CompressionLayer;
+use crate::config::Config;
+use crate::error::{ApiError, ErrorCode};
+use crate::handlers::{auth, docs, health, keypair, keys, message, metrics, precompile, rent, rpc, token, transfer};
+use crate::middleware::allow::advertise_methods;
+use crate::middleware::api_key::require_api_key;
+use crate::middleware::features::require_feature;
+use crate::middleware::limits::{enforce_timeout, limit_body};
+use crate::middleware::metrics::track_metrics;
+use crate::middleware::rate_limit::rate_limit;
+use crate::middleware::request_id::trace_request;
+use crate::middleware::status_mode::apply_status_mode;
+use crate::middleware::api_key::Scope;
+use crate::models::responses::Versioned;
+use crate::state::AppState;
+
+/// API version a request was routed under. Every endpoint is served at `/v1/...` and
+/// `/v2/...`; the unversioned paths are kept for older clients and answer like `/v1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+            ApiVersion::V2 => "/v2",
+        }
+    }
+
+    /// Builds the response for this version from `value`.
+    pub fn shape<T, V1, V2>(self, value: T, v1: impl FnOnce(T) -> V1, v2: impl FnOnce(T) -> V2) -> Versioned<V1, V2> {
+        match self {
+            ApiVersion::V1 => Versioned::V1(v1(value)),
+            ApiVersion::V2 => Versioned::V2(v2(value)),
+        }
+    }
+
+    /// For responses whose v2 shape is a plain conversion of the v1 one.
+    pub fn convert<V1, V2: From<V1>>(self, response: V1) -> Versioned<V1, V2> {
+        self.shape(response, |v1| v1, V2::from)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<ApiVersion>().copied().unwrap_or_default())
+    }
+}
+
+/// `path` without its `/v1` or `/v2` prefix, for checks keyed by the endpoint itself.
+pub fn unversioned(path: &str) -> &str {
+    ApiVersion::ALL
+        .iter()
+        .filter_map(|version| path.strip_prefix(version.prefix()))
+        .find(|rest| rest.starts_with('/'))
+        .unwrap_or(path)
+}
+
+/// One endpoint, declared once: the router, the API key check and the OpenAPI security
+/// requirements are all derived from these.
+pub struct Endpoint {
+    pub method: Method,
+    /// Route pattern in axum syntax, e.g. `/keys/:key_id`.
+    pub path: &'static str,
+    /// Scope an API key needs; `None` for public endpoints.
+    pub scope: Option<Scope>,
+    /// Whether the endpoint appears in `/openapi.json`.
+    pub documented: bool,
+    /// JSON-RPC method name on `/rpc`, if the endpoint is exposed there.
+    pub rpc: Option<&'static str>,
+    handler: MethodRouter<AppState>,
+}
+
+impl Endpoint {
+    pub fn new<H, T>(method: Method, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(method.clone()).expect("routable method");
+        Self {
+            method,
+            path,
+            scope: None,
+            documented: true,
+            rpc: None,
+            handler: routing::on(filter, handler),
+        }
+    }
+
+    pub fn get<H: Handler<T, AppState>, T: 'static>(path: &'static str, handler: H) -> Self {
+        Self::new(Method::GET, path, handler)
+    }
+
+    pub fn post<H: Handler<T, AppState>, T: 'static>(path: &'static str, handler: H) -> Self {
+        Self::new(Method::POST, path, handler)
+    }
+
+    pub fn delete<H: Handler<T, AppState>, T: 'static>(path: &'static str, handler: H) -> Self {
+        Self::new(Method::DELETE, path, handler)
+    }
+
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    pub fn undocumented(mut self) -> Self {
+        self.documented = false;
+        self
+    }
+
+    pub fn rpc(mut self, method: &'static str) -> Self {
+        self.rpc = Some(method);
+        self
+    }
+}
+
+/// Every endpoint the server exposes.
+pub fn endpoints() -> Vec<Endpoint> {
+    vec![
+        // Keypair operations
+        Endpoint::post("/keypair", keypair::generate_keypair).rpc("generateKeypair").scope(Scope::Keypair),
+        Endpoint::post("/keypair/decrypt", keypair::decrypt_keystore).rpc("decryptKeystore").scope(Scope::Keypair),
+        Endpoint::post("/keypair/split", keypair::split_keypair).rpc("splitKeypair").scope(Scope::Keypair),
+        Endpoint::post("/keypair/combine", keypair::combine_shares).rpc("combineShares").scope(Scope::Keypair),
+
+        // Vault key management
+        Endpoint::get("/keys", keys::list_keys).rpc("listKeys").scope(Scope::Keypair),
+        Endpoint::get("/keys/:key_id", keys::get_key).rpc("getKey").scope(Scope::Keypair),
+        Endpoint::delete("/keys/:key_id", keys::delete_key).rpc("deleteKey").scope(Scope::Keypair),
+        Endpoint::post("/keys/:key_id/disable", keys::disable_key).rpc("disableKey").scope(Scope::Keypair),
+        Endpoint::post("/keys/:key_id/enable", keys::enable_key).rpc("enableKey").scope(Scope::Keypair),
+
+        // Message operations
+        Endpoint::post("/message/sign", message::sign_message).rpc("signMessage").scope(Scope::Message),
+        Endpoint::post("/message/verify", message::verify_message).rpc("verifyMessage").scope(Scope::Message),
+        Endpoint::post("/message/sign-batch", message::sign_batch).rpc("signBatch").scope(Scope::Message),
+        Endpoint::post("/message/verify-batch", message::verify_batch).rpc("verifyBatch").scope(Scope::Message),
+
+        // Sign-In With Solana
+        Endpoint::post("/auth/siws/challenge", auth::siws_challenge).rpc("siwsChallenge").scope(Scope::Message),
+        Endpoint::post("/auth/siws/verify", auth::siws_verify).rpc("siwsVerify").scope(Scope::Message),
+        Endpoint::post("/auth/siws/session", auth::siws_session).rpc("siwsSession").scope(Scope::Message),
+
+        // Token operations
+        Endpoint::post("/token/create", token::create_token).rpc("createToken").scope(Scope::Token),
+        Endpoint::post("/token/mint", token::mint_token).rpc("mintToken").scope(Scope::Token),
+
+        // Signature verification precompiles
+        Endpoint::post("/precompile/ed25519", precompile::build_ed25519_instruction).rpc("buildEd25519Instruction").scope(Scope::Message),
+        Endpoint::post("/precompile/secp256k1", precompile::build_secp256k1_instruction).rpc("buildSecp256k1Instruction").scope(Scope::Message),
+
+        // Transfer operations
+        Endpoint::post("/send/sol", transfer::send_sol).rpc("sendSol").scope(Scope::Transfer),
+        Endpoint::post("/send/token", transfer::send_token).rpc("sendToken").scope(Scope::Transfer),
+
+        // JSON-RPC over the operations above; each call is checked like its REST request
+        Endpoint::post("/rpc", rpc::handle_rpc),
+
+        // Rent
+        Endpoint::get("/rent/:space", rent::rent_exempt_minimum).rpc("getRentExemptMinimum"),
+
+        // API documentation
+        Endpoint::get("/openapi.json", docs::openapi_json).undocumented(),
+        Endpoint::get("/docs", docs::docs_page).undocumented(),
+        Endpoint::get("/docs/redoc.standalone.js", docs::redoc_bundle).undocumented(),
+
+        // Operations
+        Endpoint::get("/healthz", health::healthz),
+        Endpoint::get("/readyz", health::readyz),
+        Endpoint::get("/version", health::version),
+        Endpoint::get("/metrics", metrics::render_metrics).undocumented(),
+    ]
+}
+
+/// The whole application as served: every endpoint, unversioned (the v1 shapes older
+/// clients rely on), under `/v1` and under `/v2`, behind the middleware stack configured
+/// from `config`. Only fails for settings `Config::validate` rejects.
+pub fn app(config: &Config, state: AppState) -> Result<Router, String> {
+    // CORS policy from [cors]; the defaults allow any origin, method and header
+    let cors = config.cors_layer()?;
+    // gzip/brotli as negotiated with Accept-Encoding; with both off responses pass through as-is
+    let compress = config.server.compression;
+    let compression = CompressionLayer::new().gzip(compress).br(compress);
+
+    let app = versioned_router(endpoints)
+        // Fallback for everything else
+        .fallback(handle_fallback)
+        .layer(
+            ServiceBuilder::new()
+                // Outermost so every response, preflights included, carries X-Request-Id
+                .layer(from_fn(trace_request))
+                .layer(compression)
+                .layer(from_fn_with_state(state.clone(), advertise_methods))
+                .layer(cors)
+                .layer(from_fn_with_state(state.clone(), apply_status_mode))
+                // Inside the status mode layer so metrics see the real error status
+                .layer(from_fn(track_metrics))
+                .layer(from_fn_with_state(state.clone(), require_feature))
+                .layer(from_fn_with_state(state.clone(), require_api_key))
+                // So the timeout also covers reading the body
+                .layer(from_fn_with_state(state.clone(), enforce_timeout))
+                .layer(from_fn_with_state(state.clone(), limit_body))
+                // Innermost, since classifying keystore signs needs the buffered body
+                .layer(from_fn_with_state(state.clone(), rate_limit)),
+        )
+        // `limit_body` already buffered the body; this only keeps axum's own 2 MiB cap from
+        // rejecting bodies under a larger configured limit
+        .layer(DefaultBodyLimit::max(config.limits.max_body_bytes))
+        .with_state(state);
+    // `/rpc` sends each call back through this same app, middleware included
+    Ok(app.clone().layer(Extension(rpc::Dispatcher::new(app))))
+}
+
+// Unknown paths get the same JSON error format as every other failure
+async fn handle_fallback() -> ApiError {
+    ApiError::new(ErrorCode::NotFound, "Endpoint not found")
+}
+
+/// Mounts the endpoints at their unversioned paths and under each version prefix.
+pub fn versioned_router(endpoints: impl Fn() -> Vec<Endpoint>) -> Router<AppState> {
+    ApiVersion::ALL.into_iter().fold(router(endpoints()), |app, version| {
+        app.nest(version.prefix(), router(endpoints()).layer(Extension(version)))
+    })
+}
+
+/// Builds the router for `endpoints`. Each path also answers `OPTIONS` with its `Allow`
+/// header, and other methods with `METHOD_NOT_ALLOWED` and the same header. Browser
+/// preflights are answered by the CORS layer first; see `middleware::allow`.
+pub fn router(endpoints: Vec<Endpoint>) -> Router<AppState> {
+    let mut paths: BTreeMap<&'static str, (MethodRouter<AppState>, Vec<Method>)> = BTreeMap::new();
+    for endpoint in endpoints {
+        match paths.remove(endpoint.path) {
+            // `merge` panics on a method declared twice for the same path
+            Some((handlers, mut methods)) => {
+                methods.push(endpoint.method);
+                paths.insert(endpoint.path, (handlers.merge(endpoint.handler), methods));
+            }
+            None => {
+                paths.insert(endpoint.path, (endpoint.handler, vec![endpoint.method]));
+            }
+        }
+    }
+
+    paths.into_iter().fold(Router::new(), |router, (path, (handlers, methods))| {
+        let allow = allow_header(&methods);
+        let preflight_allow = allow.clone();
+        let handlers = handlers
+            .options(move || async move { (StatusCode::NO_CONTENT, [(header::ALLOW, preflight_allow)]) })
+            .fallback(move || async move {
+                let error = ApiError::new(ErrorCode::MethodNotAllowed, "Method not allowed");
+                ([(header::ALLOW, allow)], error).into_response()
+            });
+        router.route(path, handlers)
+    })
+}
+
+/// Scopes and allowed methods of the registered endpoints, looked up by the matched
+/// route pattern. Versioned routes share the entry of their unversioned path.
+#[derive(Debug, Default)]
+pub struct RouteTable {
+    scopes: HashMap<&'static str, Vec<(Method, Scope)>>,
+    allow: HashMap<&'static str, HeaderValue>,
+    rpc: HashMap<&'static str, (Method, &'static str)>,
+}
+
+impl RouteTable {
+    pub fn new(endpoints: &[Endpoint]) -> Self {
+        let mut scopes: HashMap<&'static str, Vec<(Method, Scope)>> = HashMap::new();
+        let mut methods: HashMap<&'static str, Vec<Method>> = HashMap::new();
+        let mut rpc = HashMap::new();
+        for endpoint in endpoints {
+            if let Some(name) = endpoint.rpc {
+                rpc.insert(name, (endpoint.method.clone(), endpoint.path));
+            }
+            if let Some(scope) = endpoint.scope {
+                scopes.entry(endpoint.path).or_default().push((endpoint.method.clone(), scope));
+            }
+            methods.entry(endpoint.path).or_default().push(endpoint.method.clone());
+        }
+        let allow = methods
+            .into_iter()
+            .map(|(path, methods)| (path, allow_header(&methods)))
+            .collect();
+        Self { scopes, allow, rpc }
+    }
+
+    /// HTTP method and route pattern behind a JSON-RPC method.
+    pub fn rpc_method(&self, name: &str) -> Option<(&Method, &'static str)> {
+        self.rpc.get(name).map(|(method, path)| (method, *path))
+    }
+
+    /// `Allow` header value for a route.
+    pub fn allow(&self, route: &str) -> Option<&HeaderValue> {
+        self.allow.get(unversioned(route))
+    }
+
+    pub fn scope(&self, route: &str, method: &Method) -> Option<Scope> {
+        // HEAD is served by the GET handler
+        let method = if method == Method::HEAD { &Method::GET } else { method };
+        self.scopes
+            .get(unversioned(route))?
+            .iter()
+            .find(|(declared, _)| declared == method)
+            .map(|(_, scope)| *scope)
+    }
+}
+
+fn allow_header(methods: &[Method]) -> HeaderValue {
+    let mut allowed: Vec<&str> = methods.iter().map(Method::as_str).collect();
+    if methods.contains(&Method::GET) {
+        allowed.push("HEAD");
+    }
+    allowed.push("OPTIONS");
+    HeaderValue::from_str(&allowed.join(", ")).expect("method names are valid header values")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use axum::body::{self, Body};
+    use axum::extract::Request;
+    use axum::http::Response;
+    use tower::Service;
+    use crate::signer::LocalSigner;
+    use crate::vault::MemoryKeyStore;
+
+    async fn send(method: Method, path: &str) -> Response<Body> {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        let request = Request::builder().method(method).uri(path).body(Body::empty()).unwrap();
+        versioned_router(endpoints).with_state(state).call(request).await.unwrap_or_else(|e| match e {})
+    }
+
+    fn allow(response: &Response<Body>) -> &str {
+        response.headers()[header::ALLOW].to_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn undeclared_methods_get_405_with_allow() {
+        for path in ["/keypair", "/v1/keypair", "/v2/keypair"] {
+            let response = send(Method::PUT, path).await;
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{}", path);
+            assert_eq!(allow(&response), "POST, OPTIONS");
+
+            let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["code"], "METHOD_NOT_ALLOWED");
+        }
+
+        let response = send(Method::POST, "/v2/keys/abc").await;
+        assert_eq!(allow(&response), "GET, DELETE, HEAD, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn options_lists_the_methods_of_the_path() {
+        let response = send(Method::OPTIONS, "/v1/keys/abc").await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(allow(&response), "GET, DELETE, HEAD, OPTIONS");
+
+        assert_eq!(allow(&send(Method::OPTIONS, "/healthz").await), "GET, HEAD, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn unknown_paths_are_not_method_errors() {
+        assert_eq!(send(Method::GET, "/v3/keypair").await.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn post(path: &str, body: serde_json::Value) -> serde_json::Value {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        let request = Request::builder().method(Method::POST).uri(path).body(Body::from(body.to_string())).unwrap();
+        let response = versioned_router(endpoints).with_state(state).call(request).await.unwrap_or_else(|e| match e {});
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    // `data` of a successful response from the unversioned, /v1 and /v2 paths
+    async fn post_all(path: &str, body: serde_json::Value) -> [serde_json::Value; 3] {
+        let mut responses = Vec::new();
+        for path in [path.to_string(), format!("/v1{}", path), format!("/v2{}", path)] {
+            let response = post(&path, body.clone()).await;
+            assert_eq!(response["success"], true, "{}: {}", path, response);
+            responses.push(response["data"].clone());
+        }
+        responses.try_into().unwrap()
+    }
+
+    fn base64(bytes: &[u8]) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    fn pubkey(byte: u8) -> String {
+        solana_sdk::pubkey::Pubkey::new_from_array([byte; 32]).to_string()
+    }
+
+    fn sorted_keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn sol_transfers_keep_their_shape_per_version() {
+        let (from, to) = (pubkey(1), pubkey(2));
+        let [unversioned, v1, v2] = post_all("/send/sol", serde_json::json!({ "from": from, "to": to, "lamports": 1000 })).await;
+
+        let system = "11111111111111111111111111111111";
+        assert_eq!(
+            v1,
+            serde_json::json!({ "program_id": system, "accounts": [from, to], "instruction_data": "AgAAAOgDAAAAAAAA" })
+        );
+        assert_eq!(unversioned, v1);
+        assert_eq!(
+            v2,
+            serde_json::json!({
+                "programId": system,
+                "accounts": [
+                    { "pubkey": from, "isSigner": true, "isWritable": true },
+                    { "pubkey": to, "isSigner": false, "isWritable": true },
+                ],
+                "instructionData": "AgAAAOgDAAAAAAAA",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn token_transfers_keep_their_shape_per_version() {
+        let (destination, mint, owner) = (pubkey(1), pubkey(2), pubkey(3));
+        let body = serde_json::json!({ "destination": destination, "mint": mint, "owner": owner, "amount": 5 });
+        let [unversioned, v1, v2] = post_all("/send/token", body).await;
+
+        // The owner doubles as the source account, and is passed again as the multisig signer
+
+        let owner_key = owner.parse().unwrap();
+        let data = base64(
+            &spl_token::instruction::transfer(&spl_token::id(), &owner_key, &destination.parse().unwrap(), &owner_key, &[], 5)
+                .unwrap()
+                .data,
+        );
+        let token = spl_token::id().to_string();
+        assert_eq!(
+            v1,
+            serde_json::json!({
+                "program_id": token,
+                "accounts": [
+                    { "pubkey": owner, "isSigner": false },
+                    { "pubkey": destination, "isSigner": false },
+                    { "pubkey": owner, "isSigner": false },
+                    { "pubkey": owner, "isSigner": true },
+                ],
+                "instruction_data": data,
+            })
+        );
+        assert_eq!(unversioned, v1);
+        assert_eq!(
+            v2,
+            serde_json::json!({
+                "programId": token,
+                "accounts": [
+                    { "pubkey": owner, "isSigner": false, "isWritable": true },
+                    { "pubkey": destination, "isSigner": false, "isWritable": true },
+                    { "pubkey": owner, "isSigner": false, "isWritable": false },
+                    { "pubkey": owner, "isSigner": true, "isWritable": false },
+                ],
+                "instructionData": data,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn token_creation_keeps_its_shape_per_version() {
+        let (authority, mint) = (pubkey(1), pubkey(2));
+        let body = serde_json::json!({ "mintAuthority": authority, "mint": mint, "decimals": 6 });
+        let [unversioned, v1, v2] = post_all("/token/create", body).await;
+
+        let authority_key = authority.parse().unwrap();
+        let instruction = spl_token::instruction::initialize_mint(
+            &spl_token::id(),
+            &mint.parse().unwrap(),
+            &authority_key,
+            Some(&authority_key),
+            6,
+        )
+        .unwrap();
+        let data = base64(&instruction.data);
+        let token = spl_token::id().to_string();
+        // v1 carries only the mint, as an object
+        assert_eq!(
+            v1,
+            serde_json::json!({
+                "program_id": token,
+                "accounts": { "pubkey": mint, "is_signer": false, "is_writable": true },
+                "instruction_data": data,
+            })
+        );
+        assert_eq!(unversioned, v1);
+        assert_eq!(
+            v2,
+            serde_json::json!({
+                "programId": token,
+                "accounts": [
+                    { "pubkey": mint, "isSigner": false, "isWritable": true },
+                    { "pubkey": solana_sdk::sysvar::rent::id().to_string(), "isSigner": false, "isWritable": false },
+                ],
+                "instructionData": data,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn token_minting_keeps_its_shape_per_version() {
+        let (mint, destination, authority) = (pubkey(1), pubkey(2), pubkey(3));
+        let body = serde_json::json!({ "mint": mint, "destination": destination, "authority": authority, "amount": 9 });
+        let [unversioned, v1, v2] = post_all("/token/mint", body).await;
+
+        // As with transfers, the authority is listed again as the multisig signer
+
+        let authority_key = authority.parse().unwrap();
+        let data = base64(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.parse().unwrap(),
+                &destination.parse().unwrap(),
+                &authority_key,
+                &[],
+                9,
+            )
+            .unwrap()
+            .data,
+        );
+        let token = spl_token::id().to_string();
+        assert_eq!(
+            v1,
+            serde_json::json!({
+                "program_id": token,
+                "accounts": [
+                    { "pubkey": mint, "is_signer": false, "is_writable": true },
+                    { "pubkey": destination, "is_signer": false, "is_writable": true },
+                    { "pubkey": authority, "is_signer": false, "is_writable": false },
+                    { "pubkey": authority, "is_signer": true, "is_writable": false },
+                ],
+                "instruction_data": data,
+            })
+        );
+        assert_eq!(unversioned, v1);
+        assert_eq!(
+            v2,
+            serde_json::json!({
+                "programId": token,
+                "accounts": [
+                    { "pubkey": mint, "isSigner": false, "isWritable": true },
+                    { "pubkey": destination, "isSigner": false, "isWritable": true },
+                    { "pubkey": authority, "isSigner": false, "isWritable": false },
+                    { "pubkey": authority, "isSigner": true, "isWritable": false },
+                ],
+                "instructionData": data,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn precompile_instructions_keep_their_shape_per_version() {
+        let ed25519 = serde_json::json!({
+            "signatures": [{ "message": "hello", "secret": solana_sdk::signer::keypair::Keypair::new().to_base58_string() }],
+        });
+        let secp256k1 = serde_json::json!({ "signatures": [{ "message": "hello", "secret": "01".repeat(32) }] });
+
+        for (path, body, program_id) in [
+            ("/precompile/ed25519", ed25519, solana_sdk::ed25519_program::id()),
+            ("/precompile/secp256k1", secp256k1, solana_sdk::secp256k1_program::id()),
+        ] {
+            let [unversioned, v1, v2] = post_all(path, body).await;
+            assert_eq!(sorted_keys(&v1), ["accounts", "instruction_data", "program_id"], "{}", path);
+            assert_eq!(v1["program_id"], program_id.to_string());
+            assert_eq!(v1["accounts"], serde_json::json!([]));
+            assert_eq!(sorted_keys(&unversioned), sorted_keys(&v1));
+
+            assert_eq!(sorted_keys(&v2), ["accounts", "instructionData", "programId"], "{}", path);
+            assert_eq!(v2["programId"], program_id.to_string());
+            assert_eq!(v2["accounts"], serde_json::json!([]));
+            assert_eq!(v2["instructionData"], v1["instruction_data"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn instruction_options_are_v2_only() {
+        let body = serde_json::json!({ "from": pubkey(1), "to": pubkey(2), "lamports": 1000, "dataEncoding": "hex", "decoded": true });
+        for path in ["/send/sol", "/v1/send/sol"] {
+            let response = post(path, body.clone()).await;
+            assert_eq!(response["code"], "INVALID_FIELD", "{}", path);
+            assert_eq!(response["field"], "dataEncoding");
+        }
+
+        let response = post("/v2/send/sol", body).await;
+        assert_eq!(response["data"]["instructionData"], "02000000e803000000000000");
+        assert_eq!(response["data"]["decoded"]["type"], "transfer");
+    }
+
+    // The served app for `config`, as main builds it
+    fn configured_app(config: &Config) -> Router {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+            .with_limits(config.request_limits().unwrap())
+            .with_routes(RouteTable::new(&endpoints()));
+        app(config, state).unwrap()
+    }
+
+    fn header<'a>(response: &'a Response<Body>, name: &str) -> Option<&'a str> {
+        response.headers().get(name).map(|value| value.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn cors_allows_only_the_configured_origins() {
+        let mut config = Config::default();
+        config.cors.allowed_origins = vec!["https://app.example.com".to_string()];
+        config.cors.allowed_methods = vec!["POST".to_string()];
+        config.cors.allowed_headers = vec!["content-type".to_string(), "x-api-key".to_string()];
+        config.cors.allow_credentials = true;
+        let mut app = configured_app(&config);
+
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/v2/keypair")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-api-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.call(preflight("https://app.example.com")).await.unwrap_or_else(|e| match e {});
+        assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+        assert_eq!(header(&response, "access-control-allow-credentials"), Some("true"));
+        assert_eq!(header(&response, "access-control-allow-methods"), Some("POST"));
+
+        let response = app.call(preflight("https://evil.example.com")).await.unwrap_or_else(|e| match e {});
+        assert_eq!(header(&response, "access-control-allow-origin"), None);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/keypair")
+            .header(header::ORIGIN, "https://app.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap_or_else(|e| match e {});
+        assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+        assert!(header(&response, "access-control-expose-headers").unwrap().contains("x-request-id"));
+    }
+
+    #[test]
+    fn cors_credentials_need_explicit_origins() {
+        let mut config = Config::default();
+        config.auth.disabled = true;
+        config.cors.allow_credentials = true;
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("allow_credentials"), "{}", error);
+
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        assert!(app(&config, state).is_err());
+    }
+
+    #[tokio::test]
+    async fn oversized_bodies_get_413() {
+        let mut config = Config::default();
+        config.limits.max_body_bytes = 64;
+        let mut app = configured_app(&config);
+        let body = format!(r#"{{"message": "{}", "secret": "x"}}"#, "a".repeat(100));
+
+        // Announced with Content-Length, and discovered while reading
+        for declared in [true, false] {
+            let mut request = Request::builder()
+                .method(Method::POST)
+                .uri("/v1/message/sign")
+                .header(&crate::middleware::status_mode::ERROR_MODE_HEADER, "http");
+            if declared {
+                request = request.header(header::CONTENT_LENGTH, body.len());
+            }
+            let response = app.call(request.body(Body::from(body.clone())).unwrap()).await.unwrap_or_else(|e| match e {});
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "declared: {}", declared);
+            let response = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&response).unwrap();
+            assert_eq!(response["code"], "PAYLOAD_TOO_LARGE");
+        }
+    }
+
+    // A request body that never arrives
+    struct Stalled;
+
+    impl axum::body::HttpBody for Stalled {
+        type Data = axum::body::Bytes;
+        type Error = Infallible;
+
+        fn poll_frame(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+            std::task::Poll::Pending
+        }
+    }
+
+    #[tokio::test]
+    async fn route_timeouts_apply_under_every_version() {
+        let mut config = Config::default();
+        config.limits.route_timeouts_ms.insert("/keypair".to_string(), 50);
+        let mut app = configured_app(&config);
+        let stalled = |path: &str| Request::builder().method(Method::POST).uri(path).body(Body::new(Stalled)).unwrap();
+
+        for path in ["/keypair", "/v1/keypair", "/v2/keypair"] {
+            let response = tokio::time::timeout(std::time::Duration::from_secs(5), app.call(stalled(path)))
+                .await
+                .expect("the route timeout did not fire")
+                .unwrap_or_else(|e| match e {});
+            let response = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&response).unwrap();
+            assert_eq!(response["code"], "TIMEOUT", "{}", path);
+        }
+
+        // Other routes keep the 30s default
+        let pending = tokio::time::timeout(std::time::Duration::from_millis(300), app.call(stalled("/v2/message/sign")));
+        assert!(pending.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn responses_are_compressed_as_negotiated() {
+        let get = |encoding: &str| {
+            Request::builder().uri("/v2/openapi.json").header(header::ACCEPT_ENCODING, encoding).body(Body::empty()).unwrap()
+        };
+
+        let mut app = configured_app(&Config::default());
+        for (accept, expected) in [("gzip", Some("gzip")), ("br", Some("br")), ("br;q=0.5, gzip", Some("gzip")), ("identity", None)] {
+            let response = app.call(get(accept)).await.unwrap_or_else(|e| match e {});
+            assert_eq!(header(&response, "content-encoding"), expected, "{}", accept);
+        }
+
+        let mut config = Config::default();
+        config.server.compression = false;
+        let response = configured_app(&config).call(get("gzip, br")).await.unwrap_or_else(|e| match e {});
+        assert_eq!(header(&response, "content-encoding"), None);
+    }
+
+    #[test]
+    fn unversioned_strips_only_whole_prefixes() {
+        assert_eq!(unversioned("/v1/keys/:key_id"), "/keys/:key_id");
+        assert_eq!(unversioned("/v2/rpc"), "/rpc");
+        assert_eq!(unversioned("/keypair"), "/keypair");
+        for path in ["/v1", "/v1x/keys", "/v3/keys", "/keys/v1/x"] {
+            assert_eq!(unversioned(path), path);
+        }
+    }
+
+    #[test]
+    fn route_table_serves_versioned_routes() {
+        let table = RouteTable::new(&endpoints());
+        assert_eq!(table.scope("/v2/keys/:key_id", &Method::DELETE), Some(Scope::Keypair));
+        assert_eq!(table.scope("/keys/:key_id", &Method::HEAD), Some(Scope::Keypair));
+        assert_eq!(table.scope("/healthz", &Method::GET), None);
+        assert_eq!(table.allow("/v1/send/sol").unwrap(), "POST, OPTIONS");
+        assert_eq!(table.rpc_method("sendSol"), Some((&Method::POST, "/send/sol")));
+    }
+}
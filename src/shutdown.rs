@@ -0,0 +1,158 @@
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use crate::metrics::metrics;
+use crate::state::AppState;
+
+/// Resolves on the first SIGINT or SIGTERM with the signal's name.
+pub async fn signal() -> &'static str {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+/// The server's graceful shutdown future. Once `signal` resolves, readiness fails while the
+/// server keeps serving for `delay`, so load balancers can stop routing to it. Then this
+/// resolves, which stops the server accepting connections, and starts `drain_deadline`
+/// through `started`.
+pub async fn requested(
+    state: AppState,
+    signal: impl Future<Output = &'static str>,
+    delay: Duration,
+    started: oneshot::Sender<()>,
+) {
+    let signal = signal.await;
+    state.start_draining();
+    tracing::info!(signal, delay_secs = delay.as_secs(), "Shutdown requested; failing readiness before draining");
+    tokio::time::sleep(delay).await;
+    tracing::info!(in_flight = metrics().http_requests_in_flight.get(), "Draining in-flight requests");
+    let _ = started.send(());
+}
+
+/// Resolves `timeout` after draining starts, with the number of requests still in flight
+/// then, which are dropped. Never resolves if draining does not start, so it can race the
+/// server future without ending it early.
+pub async fn drain_deadline(started: oneshot::Receiver<()>, timeout: Duration) -> i64 {
+    if started.await.is_err() {
+        return std::future::pending().await;
+    }
+    tokio::time::sleep(timeout).await;
+    metrics().http_requests_in_flight.get()
+}
+
+/// Logs the final request counts and flushes the log output before the process exits.
+/// `dropped` is what `drain_deadline` reported, or 0 when every request finished.
+pub fn finish(dropped: i64) {
+    tracing::info!(requests_served = metrics().requests_served(), requests_dropped = dropped, "Server stopped");
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use axum::{middleware, routing, Router};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use crate::middleware::metrics::track_metrics;
+    use crate::routes;
+    use crate::signer::LocalSigner;
+    use crate::vault::MemoryKeyStore;
+
+    const DELAY: Duration = Duration::from_millis(300);
+    const TIMEOUT: Duration = Duration::from_millis(300);
+
+    // Sends a bare HTTP/1.1 GET and returns the raw response
+    async fn get(address: std::net::SocketAddr, path: &str) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(address).await?;
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn fails_readiness_then_drains_until_the_deadline() {
+        let vault = Arc::new(MemoryKeyStore::new());
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)));
+        let probes = routes::endpoints().into_iter().filter(|endpoint| endpoint.path == "/readyz").collect();
+        let app = routes::router(probes)
+            .merge(Router::new().route("/slow", routing::get(|| tokio::time::sleep(Duration::from_secs(30)))))
+            .layer(middleware::from_fn(track_metrics))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (signal_tx, signal_rx) = oneshot::channel::<()>();
+        let (drain_started, drain_rx) = oneshot::channel();
+        let signal = async move {
+            let _ = signal_rx.await;
+            "test"
+        };
+        let server = axum::serve(listener, app)
+            .with_graceful_shutdown(requested(state.clone(), signal, DELAY, drain_started));
+        let server = tokio::spawn(async move {
+            tokio::select! {
+                _ = server => None,
+                dropped = drain_deadline(drain_rx, TIMEOUT) => Some(dropped),
+            }
+        });
+
+        assert!(get(address, "/readyz").await.unwrap().starts_with("HTTP/1.1 200"));
+        let slow = tokio::spawn(get(address, "/slow"));
+        // Let the slow request reach its handler
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let started = Instant::now();
+        signal_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Still serving during the delay, but no longer ready
+        assert!(state.is_draining());
+        assert!(get(address, "/readyz").await.unwrap().starts_with("HTTP/1.1 503"));
+
+        // The slow request outlives the deadline, so the deadline ends the server and
+        // reports it dropped
+        let dropped = server.await.unwrap().expect("the server stopped before the drain deadline");
+        assert!(dropped >= 1, "{}", dropped);
+        assert!(started.elapsed() >= DELAY + TIMEOUT);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(get(address, "/readyz").await.is_err(), "still accepting after the drain started");
+        slow.abort();
+    }
+
+    #[tokio::test]
+    async fn drain_deadline_waits_for_the_drain_to_start() {
+        let (started, receiver) = oneshot::channel();
+        let deadline = tokio::spawn(drain_deadline(receiver, Duration::from_millis(50)));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(!deadline.is_finished());
+
+        started.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), deadline).await.unwrap().unwrap();
+
+        // A shutdown that never starts never hits the deadline
+        let (started, receiver) = oneshot::channel::<()>();
+        drop(started);
+        let deadline = drain_deadline(receiver, Duration::from_millis(1));
+        assert!(tokio::time::timeout(Duration::from_millis(100), deadline).await.is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use solana_sdk::signer::Signer;
+use crate::vault::KeyStore;
+use super::{SignedMessage, SignerBackend, SignerError, SigningKey};
+
+/// Signs in-process, resolving key handles through the server's key vault.
+pub struct LocalSigner {
+    vault: Arc<dyn KeyStore>,
+}
+
+impl LocalSigner {
+    pub fn new(vault: Arc<dyn KeyStore>) -> Self {
+        Self { vault }
+    }
+}
+
+#[async_trait]
+impl SignerBackend for LocalSigner {
+    async fn sign(&self, key: &SigningKey, message: &[u8]) -> Result<SignedMessage, SignerError> {
+        match key {
+            SigningKey::Inline(keypair) => Ok(SignedMessage {
+                pubkey: keypair.pubkey(),
+                signature: keypair.sign_message(message),
+            }),
+            SigningKey::Handle(key_id) => {
+                let keypair = self
+                    .vault
+                    .keypair(key_id)
+                    .map_err(|e| SignerError::Rejected(e.to_string()))?;
+                Ok(SignedMessage {
+                    pubkey: keypair.pubkey(),
+                    signature: keypair.sign_message(message),
+                })
+            }
+        }
+    }
+}
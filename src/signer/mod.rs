@@ -0,0 +1,77 @@
+use std::fmt;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::keypair::Keypair;
+
+pub mod local;
+pub mod remote;
+
+pub use local::LocalSigner;
+pub use remote::RemoteSigner;
+
+/// The key a signature is requested from.
+pub enum SigningKey {
+    /// Opaque key id resolved by the backend (vault entry, HSM slot, KMS key).
+    Handle(String),
+    /// Key material supplied with the request itself.
+    Inline(Keypair),
+}
+
+pub struct SignedMessage {
+    pub pubkey: Pubkey,
+    pub signature: Signature,
+}
+
+#[derive(Debug)]
+pub enum SignerError {
+    /// The backend refused the request (unknown key, disabled key, ...).
+    Rejected(String),
+    /// The backend could not be reached or answered with garbage.
+    Unavailable(String),
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::Rejected(e) => write!(f, "{}", e),
+            SignerError::Unavailable(e) => write!(f, "Signer unavailable: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+/// Produces ed25519 signatures over arbitrary bytes. Message signing and any transaction
+/// signing go through this trait so the key material can live outside the server process.
+#[async_trait]
+pub trait SignerBackend: Send + Sync {
+    async fn sign(&self, key: &SigningKey, message: &[u8]) -> Result<SignedMessage, SignerError>;
+
+    /// Checks that the backend can be reached. Used by the readiness probe.
+    async fn health(&self) -> Result<(), SignerError> {
+        Ok(())
+    }
+}
+
+/// Wire format spoken with external signers, over HTTP (`POST /sign`) or a Unix socket
+/// (one JSON object per line, one request per connection).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoteSignRequest {
+    #[serde(rename = "keyId")]
+    pub key_id: String,
+    /// Base64-encoded bytes to sign.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RemoteSignResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pubkey: Option<String>,
+    /// Base64-encoded ed25519 signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use async_trait::async_trait;
+use base64::Engine;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::header::{CONTENT_TYPE, HOST};
+use hyper::Request;
+use hyper_util::rt::TokioIo;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpStream, UnixStream};
+use super::{RemoteSignRequest, RemoteSignResponse, SignedMessage, SignerBackend, SignerError, SigningKey};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Transport {
+    Unix(PathBuf),
+    Http { authority: String, path: String },
+}
+
+/// Delegates key-handle signing to an external signer process (HSM/KMS proxy).
+/// Inline key material is still signed in-process since there is nothing to delegate.
+pub struct RemoteSigner {
+    transport: Transport,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self {
+            transport: Transport::Unix(path.into()),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// `url` is `http://host:port[/prefix]`; requests go to `<prefix>/sign`.
+    pub fn http(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("Unsupported signer URL: {}", url))?;
+        let (authority, prefix) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        if authority.is_empty() {
+            return Err(format!("Unsupported signer URL: {}", url));
+        }
+
+        Ok(Self {
+            transport: Transport::Http {
+                authority: authority.to_string(),
+                path: format!("{}/sign", prefix),
+            },
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Parses a backend spec of the form `unix:<path>` or `http://host:port[/prefix]`.
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        match spec.strip_prefix("unix:") {
+            Some(path) => Ok(Self::unix(path)),
+            None => Self::http(spec),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn exchange(&self, payload: Vec<u8>) -> Result<Vec<u8>, SignerError> {
+        let unavailable = |e: &dyn std::fmt::Display| SignerError::Unavailable(e.to_string());
+
+        match &self.transport {
+            Transport::Unix(path) => {
+                let mut stream = UnixStream::connect(path).await.map_err(|e| unavailable(&e))?;
+                stream.write_all(&payload).await.map_err(|e| unavailable(&e))?;
+                stream.write_all(b"\n").await.map_err(|e| unavailable(&e))?;
+
+                let mut line = String::new();
+                BufReader::new(stream)
+                    .read_line(&mut line)
+                    .await
+                    .map_err(|e| unavailable(&e))?;
+                Ok(line.into_bytes())
+            }
+            Transport::Http { authority, path } => {
+                let stream = TcpStream::connect(authority.as_str()).await.map_err(|e| unavailable(&e))?;
+                let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+                    .await
+                    .map_err(|e| unavailable(&e))?;
+                tokio::spawn(conn);
+
+                let request = Request::post(path.as_str())
+                    .header(HOST, authority.as_str())
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Full::new(Bytes::from(payload)))
+                    .map_err(|e| unavailable(&e))?;
+
+                let response = sender.send_request(request).await.map_err(|e| unavailable(&e))?;
+                let body = response.into_body().collect().await.map_err(|e| unavailable(&e))?;
+                Ok(body.to_bytes().to_vec())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SignerBackend for RemoteSigner {
+    async fn sign(&self, key: &SigningKey, message: &[u8]) -> Result<SignedMessage, SignerError> {
+        let key_id = match key {
+            SigningKey::Inline(keypair) => {
+                return Ok(SignedMessage {
+                    pubkey: keypair.pubkey(),
+                    signature: keypair.sign_message(message),
+                })
+            }
+            SigningKey::Handle(key_id) => key_id,
+        };
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let request = RemoteSignRequest {
+            key_id: key_id.clone(),
+            message: engine.encode(message),
+        };
+        let payload = serde_json::to_vec(&request).map_err(|e| SignerError::Unavailable(e.to_string()))?;
+
+        let raw = tokio::time::timeout(self.timeout, self.exchange(payload))
+            .await
+            .map_err(|_| SignerError::Unavailable("timed out".to_string()))??;
+
+        let response: RemoteSignResponse = serde_json::from_slice(&raw)
+            .map_err(|_| SignerError::Unavailable("malformed signer response".to_string()))?;
+        if let Some(error) = response.error {
+            return Err(SignerError::Rejected(error));
+        }
+
+        let pubkey: Pubkey = response
+            .pubkey
+            .and_then(|pk| pk.parse().ok())
+            .ok_or_else(|| SignerError::Unavailable("malformed signer response".to_string()))?;
+        let signature = response
+            .signature
+            .and_then(|sig| engine.decode(sig).ok())
+            .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| SignerError::Unavailable("malformed signer response".to_string()))?;
+
+        // Never hand out a signature the remote side got wrong
+        if !signature.verify(pubkey.as_ref(), message) {
+            return Err(SignerError::Unavailable("signer returned an invalid signature".to_string()));
+        }
+
+        Ok(SignedMessage { pubkey, signature })
+    }
+
+    // Only checks that the signer accepts connections; a probe must not request signatures
+    async fn health(&self) -> Result<(), SignerError> {
+        let connect = async {
+            match &self.transport {
+                Transport::Unix(path) => UnixStream::connect(path).await.map(drop),
+                Transport::Http { authority, .. } => TcpStream::connect(authority.as_str()).await.map(drop),
+            }
+        };
+        tokio::time::timeout(self.timeout, connect)
+            .await
+            .map_err(|_| SignerError::Unavailable("timed out".to_string()))?
+            .map_err(|e| SignerError::Unavailable(e.to_string()))
+    }
+}
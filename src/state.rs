@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use crate::auth::siws::SiwsStore;
+use crate::config::{Cluster, FeaturesConfig};
+use crate::middleware::api_key::ApiKeys;
+use crate::middleware::limits::RequestLimits;
+use crate::middleware::status_mode::StatusMode;
+use crate::ratelimit::RateLimiter;
+use crate::routes::RouteTable;
+use crate::signer::SignerBackend;
+use crate::upstream::RpcUpstream;
+use crate::vault::KeyStore;
+
+/// Shared state handed to every handler through axum's `State` extractor.
+#[derive(Clone)]
+pub struct AppState {
+    pub vault: Arc<dyn KeyStore>,
+    pub signer: Arc<dyn SignerBackend>,
+    pub siws: Arc<SiwsStore>,
+    pub status_mode: StatusMode,
+    // `None` leaves every endpoint open
+    pub api_keys: Option<Arc<ApiKeys>>,
+    // Client certificate fingerprints mapped to identities, for mTLS
+    pub client_certs: Option<Arc<ApiKeys>>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub limits: Arc<RequestLimits>,
+    // Checked by `/readyz` when configured
+    pub rpc: Option<Arc<RpcUpstream>>,
+    pub cluster: Cluster,
+    // Program id put on token instructions: SPL Token or Token-2022
+    pub token_program: Pubkey,
+    pub rent: Rent,
+    pub features: Arc<FeaturesConfig>,
+    // Per-route scopes for the API key check
+    pub routes: Arc<RouteTable>,
+    // Set once shutdown starts; `/readyz` then reports not ready
+    pub draining: Arc<AtomicBool>,
+}
+
+impl AppState {
+    pub fn new(vault: Arc<dyn KeyStore>, signer: Arc<dyn SignerBackend>) -> Self {
+        Self {
+            vault,
+            signer,
+            siws: Arc::new(SiwsStore::new()),
+            status_mode: StatusMode::default(),
+            api_keys: None,
+            client_certs: None,
+            rate_limiter: None,
+            limits: Arc::new(RequestLimits::default()),
+            rpc: None,
+            cluster: Cluster::Mainnet,
+            token_program: spl_token::id(),
+            rent: Rent::default(),
+            features: Arc::new(FeaturesConfig::default()),
+            routes: Arc::new(RouteTable::default()),
+            draining: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_status_mode(mut self, status_mode: StatusMode) -> Self {
+        self.status_mode = status_mode;
+        self
+    }
+
+    pub fn with_api_keys(mut self, api_keys: ApiKeys) -> Self {
+        self.api_keys = Some(Arc::new(api_keys));
+        self
+    }
+
+    pub fn with_client_certs(mut self, client_certs: ApiKeys) -> Self {
+        self.client_certs = Some(Arc::new(client_certs));
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+        self
+    }
+
+    pub fn with_limits(mut self, limits: RequestLimits) -> Self {
+        self.limits = Arc::new(limits);
+        self
+    }
+
+    pub fn with_rpc_upstream(mut self, rpc: RpcUpstream) -> Self {
+        self.rpc = Some(Arc::new(rpc));
+        self
+    }
+
+    pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    pub fn with_token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    pub fn with_rent(mut self, rent: Rent) -> Self {
+        self.rent = rent;
+        self
+    }
+
+    pub fn with_features(mut self, features: FeaturesConfig) -> Self {
+        self.features = Arc::new(features);
+        self
+    }
+
+    pub fn with_routes(mut self, routes: RouteTable) -> Self {
+        self.routes = Arc::new(routes);
+        self
+    }
+
+    pub fn start_draining(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,431 @@
+use std::convert::Infallible;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use axum::{body::Body, extract::ConnectInfo, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::server::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientHello, ResolvesServerCert,
+};
+use tokio_rustls::rustls::sign::{self, CertifiedKey};
+use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig, SignatureScheme};
+use tokio_rustls::TlsAcceptor;
+use tower::Service;
+
+// Connections that have not finished the handshake by then are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether clients must present a certificate signed by the client CA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientAuth {
+    None,
+    Optional(PathBuf),
+    Required(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// How often the certificate files are checked for changes.
+    pub reload_interval: Duration,
+    pub client_auth: ClientAuth,
+}
+
+/// Verified client certificate of the connection a request arrived on, attached as a
+/// request extension. `require_api_key` maps it to an `ApiIdentity`.
+#[derive(Debug, Clone)]
+pub struct ClientCertificate {
+    /// Hex SHA-256 of the DER certificate.
+    pub fingerprint: String,
+}
+
+impl ClientCertificate {
+    pub fn from_der(der: &[u8]) -> Self {
+        Self { fingerprint: hex::encode(Sha256::digest(der)) }
+    }
+}
+
+/// Serves the current certificate and swaps in a new one when the files change.
+pub struct CertReloader {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    current: RwLock<Arc<CertifiedKey>>,
+    modified: RwLock<Option<SystemTime>>,
+}
+
+impl CertReloader {
+    pub fn load(cert_path: &Path, key_path: &Path) -> Result<Self, String> {
+        Ok(Self {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            current: RwLock::new(Arc::new(load_certified_key(cert_path, key_path)?)),
+            modified: RwLock::new(last_modified(cert_path, key_path)),
+        })
+    }
+
+    /// Reloads the certificate if either file changed since the last load. A broken
+    /// pair (say, the key replaced before the certificate) keeps the old certificate until
+    /// the files load again.
+    pub fn reload_if_changed(&self) {
+        let modified = last_modified(&self.cert_path, &self.key_path);
+        if modified == *self.modified.read().unwrap() {
+            return;
+        }
+
+        match load_certified_key(&self.cert_path, &self.key_path) {
+            Ok(key) => {
+                *self.current.write().unwrap() = Arc::new(key);
+                *self.modified.write().unwrap() = modified;
+                tracing::info!(cert = %self.cert_path.display(), "Reloaded TLS certificate");
+            }
+            Err(e) => tracing::warn!("Keeping the current TLS certificate: {}", e),
+        }
+    }
+
+    /// Polls the certificate files every `interval` for the life of the process.
+    pub fn watch(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                self.reload_if_changed();
+            }
+        });
+    }
+}
+
+impl ResolvesServerCert for CertReloader {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+/// Builds the acceptor for `settings`, advertising HTTP/2 and HTTP/1.1 over ALPN.
+pub fn acceptor(settings: &TlsSettings) -> Result<(TlsAcceptor, Arc<CertReloader>), String> {
+    let reloader = Arc::new(CertReloader::load(&settings.cert, &settings.key)?);
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match &settings.client_auth {
+        ClientAuth::None => builder.with_no_client_auth(),
+        ClientAuth::Optional(ca) => {
+            builder.with_client_cert_verifier(AllowAnyAnonymousOrAuthenticatedClient::new(load_roots(ca)?).boxed())
+        }
+        ClientAuth::Required(ca) => {
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(load_roots(ca)?).boxed())
+        }
+    };
+    let mut config = builder.with_cert_resolver(reloader.clone());
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok((TlsAcceptor::from(Arc::new(config)), reloader))
+}
+
+/// Accepts TLS connections until `shutdown` resolves, then waits for open connections
+/// to finish their in-flight requests. Requests carry `ConnectInfo<SocketAddr>` like
+/// `axum::serve` provides, plus the `ClientCertificate` when one was presented.
+pub async fn serve(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, remote) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(connection) => connection,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let watcher = graceful.watcher();
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    tracing::debug!(%remote, "TLS handshake failed: {}", e);
+                    return;
+                }
+                Err(_) => {
+                    tracing::debug!(%remote, "TLS handshake timed out");
+                    return;
+                }
+            };
+            let client_cert = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .map(|cert| ClientCertificate::from_der(&cert.0));
+
+            let service = hyper::service::service_fn(move |mut request: hyper::Request<Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(remote));
+                if let Some(cert) = &client_cert {
+                    request.extensions_mut().insert(cert.clone());
+                }
+                // `Router` is always ready, so it can be called without `poll_ready`
+                let response = app.clone().call(request.map(Body::new));
+                async move { Ok::<_, Infallible>(response.await.unwrap_or_else(|e| match e {})) }
+            });
+
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            if let Err(e) = watcher.watch(connection).await {
+                tracing::debug!(%remote, "Connection error: {}", e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    Ok(())
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey, String> {
+    let certs = rustls_pemfile::certs(&mut open(cert_path)?)
+        .map_err(|e| format!("Invalid certificate file {}: {}", cert_path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates in {}", cert_path.display()));
+    }
+
+    let key = rustls_pemfile::read_all(&mut open(key_path)?)
+        .map_err(|e| format!("Invalid key file {}: {}", key_path.display(), e))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) | rustls_pemfile::Item::ECKey(key) => {
+                Some(PrivateKey(key))
+            }
+            _ => None,
+        })
+        .ok_or_else(|| format!("No private key in {}", key_path.display()))?;
+    let signing_key = sign::any_supported_type(&key)
+        .map_err(|_| format!("Unsupported private key in {}", key_path.display()))?;
+    if !key_matches(&certs[0], signing_key.as_ref()) {
+        return Err(format!("The key in {} does not match {}", key_path.display(), cert_path.display()));
+    }
+
+    Ok(CertifiedKey::new(certs.into_iter().map(Certificate).collect(), signing_key))
+}
+
+// Signs a probe with `key` and checks the signature against the certificate's public key
+fn key_matches(cert: &[u8], key: &dyn sign::SigningKey) -> bool {
+    const SCHEMES: [(SignatureScheme, &webpki::SignatureAlgorithm); 4] = [
+        (SignatureScheme::ECDSA_NISTP256_SHA256, &webpki::ECDSA_P256_SHA256),
+        (SignatureScheme::ECDSA_NISTP384_SHA384, &webpki::ECDSA_P384_SHA384),
+        (SignatureScheme::ED25519, &webpki::ED25519),
+        (SignatureScheme::RSA_PSS_SHA256, &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY),
+    ];
+    let probe = b"solana-http-server key check";
+
+    let Ok(cert) = webpki::EndEntityCert::try_from(cert) else {
+        return false;
+    };
+    let Some(signer) = key.choose_scheme(&SCHEMES.map(|(scheme, _)| scheme)) else {
+        return false;
+    };
+    let Some((_, algorithm)) = SCHEMES.iter().find(|(scheme, _)| *scheme == signer.scheme()) else {
+        return false;
+    };
+    signer
+        .sign(probe)
+        .is_ok_and(|signature| cert.verify_signature(algorithm, probe, &signature).is_ok())
+}
+
+fn load_roots(path: &Path) -> Result<RootCertStore, String> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .map_err(|e| format!("Invalid client CA file {}: {}", path.display(), e))?;
+    let mut roots = RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(&certs);
+    if added == 0 {
+        return Err(format!("No usable certificates in client CA file {}", path.display()));
+    }
+    Ok(roots)
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+fn last_modified(cert_path: &Path, key_path: &Path) -> Option<SystemTime> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    modified(cert_path).max(modified(key_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::middleware;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::rustls::{ClientConfig, ServerName};
+    use tokio_rustls::TlsConnector;
+    use crate::middleware::api_key::{require_api_key, ApiKeys};
+    use crate::routes::{self, RouteTable};
+    use crate::signer::LocalSigner;
+    use crate::state::AppState;
+    use crate::vault::MemoryKeyStore;
+
+    // Generated by tests/fixtures/tls/generate.sh: a CA, two `localhost` certificates and
+    // a client certificate it signed
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls").join(name)
+    }
+
+    fn certs(name: &str) -> Vec<Certificate> {
+        rustls_pemfile::certs(&mut open(&fixture(name)).unwrap()).unwrap().into_iter().map(Certificate).collect()
+    }
+
+    fn served_cert(reloader: &CertReloader) -> Certificate {
+        reloader.current.read().unwrap().cert[0].clone()
+    }
+
+    #[test]
+    fn reloads_changed_files_and_keeps_the_old_pair_when_broken() {
+        let dir = std::env::temp_dir().join(format!("solana-http-server-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (cert, key) = (dir.join("cert.pem"), dir.join("key.pem"));
+        // Bumps the modification time, which may not move between quick writes
+        let mut clock = SystemTime::now();
+        let mut write = |path: &Path, contents: &[u8]| {
+            fs::write(path, contents).unwrap();
+            clock += Duration::from_secs(10);
+            File::options().write(true).open(path).unwrap().set_modified(clock).unwrap();
+        };
+        write(&cert, &fs::read(fixture("server.pem")).unwrap());
+        write(&key, &fs::read(fixture("server.key")).unwrap());
+
+        let reloader = CertReloader::load(&cert, &key).unwrap();
+        assert_eq!(served_cert(&reloader), certs("server.pem")[0]);
+        reloader.reload_if_changed();
+        assert_eq!(served_cert(&reloader), certs("server.pem")[0]);
+
+        // The key replaced before the certificate: a mismatched pair
+        write(&key, &fs::read(fixture("server-2.key")).unwrap());
+        reloader.reload_if_changed();
+        assert_eq!(served_cert(&reloader), certs("server.pem")[0]);
+
+        write(&cert, &fs::read(fixture("server-2.pem")).unwrap());
+        reloader.reload_if_changed();
+        assert_eq!(served_cert(&reloader), certs("server-2.pem")[0]);
+
+        // A file caught mid-write
+        write(&cert, b"-----BEGIN CERTIFICATE-----\nMIIB");
+        reloader.reload_if_changed();
+        assert_eq!(served_cert(&reloader), certs("server-2.pem")[0]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn mismatched_pairs_do_not_load() {
+        let error = CertReloader::load(&fixture("server.pem"), &fixture("client.key")).err().unwrap();
+        assert!(error.contains("does not match"), "{}", error);
+    }
+
+    // Serves the API over TLS with `client_auth`, accepting the client certificate as the
+    // `automation` identity with the keypair scope
+    async fn start(client_auth: ClientAuth) -> std::net::SocketAddr {
+        let settings = TlsSettings {
+            cert: fixture("server.pem"),
+            key: fixture("server.key"),
+            reload_interval: Duration::from_secs(60),
+            client_auth,
+        };
+        let (acceptor, _) = acceptor(&settings).unwrap();
+
+        let vault = Arc::new(MemoryKeyStore::new());
+        let mut client_certs = ApiKeys::default();
+        let fingerprint = ClientCertificate::from_der(&certs("client.pem")[0].0).fingerprint;
+        client_certs.insert("automation", &fingerprint, "keypair").unwrap();
+        let state = AppState::new(vault.clone(), Arc::new(LocalSigner::new(vault)))
+            .with_routes(RouteTable::new(&routes::endpoints()))
+            .with_client_certs(client_certs);
+        let app = routes::versioned_router(routes::endpoints)
+            .layer(middleware::from_fn_with_state(state.clone(), require_api_key))
+            .with_state(state);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, acceptor, app, std::future::pending()));
+        address
+    }
+
+    // POSTs an empty object to `path` over TLS, presenting the client certificate when
+    // `with_cert`; the raw response, or the error that ended the connection
+    async fn post(address: std::net::SocketAddr, path: &str, with_cert: bool) -> Result<String, String> {
+        let mut roots = RootCertStore::empty();
+        roots.add(&certs("ca.pem")[0]).unwrap();
+        let builder = ClientConfig::builder().with_safe_defaults().with_root_certificates(roots);
+        let config = if with_cert {
+            let key = rustls_pemfile::pkcs8_private_keys(&mut open(&fixture("client.key")).unwrap()).unwrap();
+            builder.with_client_auth_cert(certs("client.pem"), PrivateKey(key[0].clone())).unwrap()
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let server_name = ServerName::try_from("localhost").unwrap();
+        let mut stream = TlsConnector::from(Arc::new(config))
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| e.to_string())?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}",
+            path
+        );
+        stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.map_err(|e| e.to_string())?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn required_client_auth_refuses_clients_without_a_certificate() {
+        let address = start(ClientAuth::Required(fixture("ca.pem"))).await;
+
+        // TLS 1.3 finishes the client's side of the handshake before the server checks
+        match post(address, "/keypair", false).await {
+            Err(_) => {}
+            Ok(response) => assert!(!response.starts_with("HTTP/1.1"), "{}", response),
+        }
+        let response = post(address, "/keypair", true).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn client_certificates_authenticate_as_their_identity() {
+        let address = start(ClientAuth::Optional(fixture("ca.pem"))).await;
+
+        let response = post(address, "/keypair", true).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        // The identity only holds the keypair scope
+        let response = post(address, "/send/sol", true).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+
+        // Without a certificate the connection is accepted, but not authenticated
+        let response = post(address, "/keypair", false).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+        let response = post(address, "/healthz", false).await.unwrap();
+        assert!(!response.starts_with("HTTP/1.1 401"), "{}", response);
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::header::{CONTENT_TYPE, HOST};
+use hyper::Request;
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug)]
+pub struct UpstreamError(pub String);
+
+impl fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RPC upstream error: {}", self.0)
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+/// Solana JSON-RPC node the server depends on. Only used for health checks so far.
+pub struct RpcUpstream {
+    host: String,
+    port: u16,
+    path: String,
+    tls: Option<TlsConnector>,
+    timeout: Duration,
+}
+
+impl RpcUpstream {
+    /// `url` is `http://host[:port][/path]` or `https://host[:port][/path]`.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let unsupported = || format!("Unsupported RPC URL: {}", url);
+
+        let (rest, tls, default_port) = if let Some(rest) = url.strip_prefix("https://") {
+            (rest, true, 443)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (rest, false, 80)
+        } else {
+            return Err(unsupported());
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| unsupported())?),
+            None => (authority, default_port),
+        };
+        if host.is_empty() {
+            return Err(unsupported());
+        }
+
+        let tls = if tls {
+            // Validate the name up front so a typo fails at startup, not on every probe
+            ServerName::try_from(host).map_err(|_| unsupported())?;
+            Some(tls_connector())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            tls,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Calls `getHealth`; the node is healthy when it answers `"ok"`.
+    pub async fn health(&self) -> Result<(), UpstreamError> {
+        let payload = json!({"jsonrpc": "2.0", "id": 1, "method": "getHealth"});
+        let reply = tokio::time::timeout(self.timeout, self.call(payload.to_string().into_bytes()))
+            .await
+            .map_err(|_| UpstreamError("timed out".to_string()))??;
+
+        let reply: Value = serde_json::from_slice(&reply)
+            .map_err(|_| UpstreamError("malformed JSON-RPC response".to_string()))?;
+        if reply.get("result").and_then(Value::as_str) == Some("ok") {
+            return Ok(());
+        }
+
+        let message = reply
+            .pointer("/error/message")
+            .and_then(Value::as_str)
+            .unwrap_or("unexpected getHealth result");
+        Err(UpstreamError(message.to_string()))
+    }
+
+    async fn call(&self, payload: Vec<u8>) -> Result<Vec<u8>, UpstreamError> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port)).await.map_err(upstream_error)?;
+
+        match &self.tls {
+            Some(connector) => {
+                let name = ServerName::try_from(self.host.as_str()).map_err(upstream_error)?;
+                let stream = connector.connect(name, stream).await.map_err(upstream_error)?;
+                self.post(stream, payload).await
+            }
+            None => self.post(stream, payload).await,
+        }
+    }
+
+    async fn post<S>(&self, stream: S, payload: Vec<u8>) -> Result<Vec<u8>, UpstreamError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(upstream_error)?;
+        tokio::spawn(conn);
+
+        let request = Request::post(self.path.as_str())
+            .header(HOST, self.host.as_str())
+            .header(CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(payload)))
+            .map_err(upstream_error)?;
+
+        let response = sender.send_request(request).await.map_err(upstream_error)?;
+        if !response.status().is_success() {
+            return Err(UpstreamError(format!("HTTP {}", response.status())));
+        }
+        let body = response.into_body().collect().await.map_err(upstream_error)?;
+        Ok(body.to_bytes().to_vec())
+    }
+}
+
+fn tls_connector() -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(ta.subject, ta.spki, ta.name_constraints)
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+fn upstream_error(e: impl fmt::Display) -> UpstreamError {
+    UpstreamError(e.to_string())
+}
@@ -0,0 +1,121 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How the `message` string of a sign/verify request maps to bytes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageEncoding {
+    #[default]
+    Utf8,
+    Hex,
+    Base64,
+    Base58,
+}
+
+impl MessageEncoding {
+    pub fn decode(self, message: &str) -> Result<Vec<u8>, String> {
+        let bytes = match self {
+            MessageEncoding::Utf8 => return Ok(message.as_bytes().to_vec()),
+            MessageEncoding::Hex => hex::decode(message).ok(),
+            MessageEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(message).ok(),
+            MessageEncoding::Base58 => bs58::decode(message).into_vec().ok(),
+        };
+        bytes.ok_or_else(|| "Invalid message encoding".to_string())
+    }
+}
+
+/// Text encoding of a signature, in requests and responses. Also used for instruction
+/// data, as `DataEncoding`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureEncoding {
+    #[default]
+    Base64,
+    Base58,
+    Hex,
+}
+
+impl SignatureEncoding {
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+            SignatureEncoding::Base58 => bs58::encode(bytes).into_string(),
+            SignatureEncoding::Hex => hex::encode(bytes),
+        }
+    }
+
+    pub fn decode(self, signature: &str) -> Result<Vec<u8>, String> {
+        let bytes = match self {
+            SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(signature).ok(),
+            SignatureEncoding::Base58 => bs58::decode(signature).into_vec().ok(),
+            SignatureEncoding::Hex => hex::decode(signature).ok(),
+        };
+        bytes.ok_or_else(|| "Invalid signature format".to_string())
+    }
+}
+
+/// Text encoding of instruction data in responses; the same choices as for signatures.
+pub type DataEncoding = SignatureEncoding;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_decode_in_each_encoding() {
+        let bytes = b"hello\x00\xff";
+        assert_eq!(MessageEncoding::Utf8.decode("héllo").unwrap(), "héllo".as_bytes());
+        assert_eq!(MessageEncoding::Hex.decode("68656c6c6f00ff").unwrap(), bytes);
+        assert_eq!(MessageEncoding::Hex.decode("68656C6C6F00FF").unwrap(), bytes);
+        assert_eq!(MessageEncoding::Base64.decode("aGVsbG8A/w==").unwrap(), bytes);
+        assert_eq!(MessageEncoding::Base58.decode(&bs58::encode(bytes).into_string()).unwrap(), bytes);
+
+        // Empty messages are valid in every encoding
+        for encoding in [MessageEncoding::Utf8, MessageEncoding::Hex, MessageEncoding::Base64, MessageEncoding::Base58] {
+            assert_eq!(encoding.decode("").unwrap(), b"", "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        for (encoding, message) in [
+            (MessageEncoding::Hex, "abc"),
+            (MessageEncoding::Hex, "zz"),
+            (MessageEncoding::Base64, "aGVsbG8"),
+            (MessageEncoding::Base64, "not base64!"),
+            (MessageEncoding::Base58, "0OIl"),
+        ] {
+            assert_eq!(encoding.decode(message).unwrap_err(), "Invalid message encoding", "{:?} {}", encoding, message);
+        }
+    }
+
+    #[test]
+    fn signatures_round_trip_in_each_encoding() {
+        let signature: Vec<u8> = (0..64).collect();
+        for (encoding, prefix) in [
+            (SignatureEncoding::Base64, "AAECAwQF"),
+            (SignatureEncoding::Base58, "1"),
+            (SignatureEncoding::Hex, "000102030405"),
+        ] {
+            let encoded = encoding.encode(&signature);
+            assert!(encoded.starts_with(prefix), "{:?} {}", encoding, encoded);
+            assert_eq!(encoding.decode(&encoded).unwrap(), signature, "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected() {
+        // Hex with an odd length, base64 with its padding cut, and base58 outside its alphabet
+        let signature = [7u8; 64];
+        let hex = SignatureEncoding::Hex.encode(&signature);
+        let base64 = SignatureEncoding::Base64.encode(&signature);
+        for (encoding, malformed) in [
+            (SignatureEncoding::Hex, &hex[1..]),
+            (SignatureEncoding::Base64, base64.trim_end_matches('=')),
+            (SignatureEncoding::Base58, hex.as_str()),
+        ] {
+            assert_eq!(encoding.decode(malformed).unwrap_err(), "Invalid signature format", "{:?} {}", encoding, malformed);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use zeroize::Zeroizing;
+
+pub const KEYSTORE_VERSION: u8 = 1;
+pub const KDF_SCRYPT: &str = "scrypt";
+pub const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+
+// scrypt cost parameters (N = 2^15, r = 8, p = 1), roughly 32 MiB of memory per derivation
+pub const DEFAULT_LOG_N: u8 = 15;
+pub const DEFAULT_R: u32 = 8;
+pub const DEFAULT_P: u32 = 1;
+// Nothing this server writes needs more, and each derivation runs on a request thread
+const MAX_LOG_N: u8 = DEFAULT_LOG_N;
+const MAX_R: u32 = DEFAULT_R;
+const MAX_P: u32 = DEFAULT_P;
+
+pub const SALT_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const KEY_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+/// Password-protected keypair blob. The secret is encrypted with AES-256-GCM under a
+/// scrypt-derived key, and the public key is bound to the ciphertext as associated data.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct Keystore {
+    pub version: u8,
+    pub pubkey: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+pub fn encrypt_keypair(keypair: &Keypair, password: &str) -> Result<Keystore, String> {
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let pubkey = keypair.pubkey().to_string();
+    let key = derive_key(password, &salt, DEFAULT_LOG_N, DEFAULT_R, DEFAULT_P)?;
+    let secret = Zeroizing::new(keypair.to_bytes());
+    let (nonce, ciphertext) = seal(&key, &*secret, pubkey.as_bytes())?;
+
+    let engine = base64::engine::general_purpose::STANDARD;
+    Ok(Keystore {
+        version: KEYSTORE_VERSION,
+        pubkey,
+        kdf: KDF_SCRYPT.to_string(),
+        kdfparams: KdfParams {
+            log_n: DEFAULT_LOG_N,
+            r: DEFAULT_R,
+            p: DEFAULT_P,
+            salt: engine.encode(salt),
+        },
+        cipher: CIPHER_AES_256_GCM.to_string(),
+        nonce: engine.encode(nonce),
+        ciphertext: engine.encode(ciphertext),
+    })
+}
+
+pub fn decrypt_keypair(keystore: &Keystore, password: &str) -> Result<Keypair, String> {
+    if keystore.version != KEYSTORE_VERSION
+        || keystore.kdf != KDF_SCRYPT
+        || keystore.cipher != CIPHER_AES_256_GCM
+    {
+        return Err("Unsupported keystore format".to_string());
+    }
+
+    let engine = base64::engine::general_purpose::STANDARD;
+    let salt = engine
+        .decode(&keystore.kdfparams.salt)
+        .map_err(|_| "Invalid keystore".to_string())?;
+    let nonce = engine
+        .decode(&keystore.nonce)
+        .map_err(|_| "Invalid keystore".to_string())?;
+    let ciphertext = engine
+        .decode(&keystore.ciphertext)
+        .map_err(|_| "Invalid keystore".to_string())?;
+
+    let params = &keystore.kdfparams;
+    let key = derive_key(password, &salt, params.log_n, params.r, params.p)?;
+    let secret = open(&key, &nonce, &ciphertext, keystore.pubkey.as_bytes())
+        .map_err(|_| "Invalid keystore password".to_string())?;
+
+    let keypair = Keypair::from_bytes(&secret).map_err(|_| "Invalid keystore".to_string())?;
+    if keypair.pubkey().to_string() != keystore.pubkey {
+        return Err("Keystore public key mismatch".to_string());
+    }
+
+    Ok(keypair)
+}
+
+pub fn derive_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+    // Cap the work factor so a crafted keystore cannot make the server burn unbounded memory
+    if log_n > MAX_LOG_N || r > MAX_R || p > MAX_P {
+        return Err("Unsupported keystore KDF parameters".to_string());
+    }
+
+    let params = scrypt::Params::new(log_n, r, p, KEY_LEN)
+        .map_err(|_| "Unsupported keystore KDF parameters".to_string())?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut *key)
+        .map_err(|_| "Failed to derive keystore key".to_string())?;
+
+    Ok(key)
+}
+
+/// Encrypts `plaintext` under `key` with a fresh random nonce, returning `(nonce, ciphertext)`.
+pub fn seal(key: &[u8; KEY_LEN], plaintext: &[u8], aad: &[u8]) -> Result<([u8; NONCE_LEN], Vec<u8>), String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Failed to encrypt keystore".to_string())?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| "Failed to encrypt keystore".to_string())?;
+
+    Ok((nonce, ciphertext))
+}
+
+/// Decrypts a `seal`ed blob. The plaintext is key material, so it is wiped when dropped.
+pub fn open(key: &[u8; KEY_LEN], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    if nonce.len() != NONCE_LEN {
+        return Err("Invalid keystore".to_string());
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Invalid keystore".to_string())?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| "Failed to decrypt keystore".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_round_trips_and_rejects_wrong_password() {
+        let keypair = Keypair::new();
+        let keystore = encrypt_keypair(&keypair, "correct horse").unwrap();
+        assert_eq!(keystore.pubkey, keypair.pubkey().to_string());
+
+        let decrypted = decrypt_keypair(&keystore, "correct horse").unwrap();
+        assert_eq!(decrypted.to_bytes(), keypair.to_bytes());
+
+        assert_eq!(decrypt_keypair(&keystore, "battery staple").unwrap_err(), "Invalid keystore password");
+    }
+
+    #[test]
+    fn tampered_public_key_fails_authentication() {
+        let mut keystore = encrypt_keypair(&Keypair::new(), "pw").unwrap();
+        keystore.pubkey = Keypair::new().pubkey().to_string();
+        assert_eq!(decrypt_keypair(&keystore, "pw").unwrap_err(), "Invalid keystore password");
+    }
+
+    #[test]
+    fn kdf_parameters_above_the_defaults_are_refused() {
+        for (log_n, r, p) in [(16, 8, 1), (15, 9, 1), (15, 8, 2)] {
+            assert_eq!(
+                derive_key("pw", &[0; SALT_LEN], log_n, r, p).unwrap_err(),
+                "Unsupported keystore KDF parameters"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_password_is_refused() {
+        assert!(encrypt_keypair(&Keypair::new(), "").is_err());
+    }
+}
@@ -1 +1,7 @@
-pub mod validation; 
+pub mod encoding;
+pub mod keystore;
+pub mod offchain;
+pub mod precompile;
+pub mod redact;
+pub mod shamir;
+pub mod validation; 
\ No newline at end of file
@@ -0,0 +1,88 @@
+use solana_sdk::offchain_message::{MessageFormat, OffchainMessage};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How message bytes are turned into the bytes that actually get signed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    /// Sign the message bytes as-is.
+    #[default]
+    Raw,
+    /// Wrap the message in the Solana off-chain message envelope
+    /// (`\xffsolana offchain` domain, header version, format and length), as produced by
+    /// Ledger and modern wallets.
+    Offchain,
+}
+
+pub const OFFCHAIN_VERSION: u8 = 0;
+
+/// Returns the bytes to sign or verify for `message`, plus the detected off-chain
+/// message format when the envelope is used.
+pub fn signing_payload(format: SigningFormat, message: &[u8]) -> Result<(Vec<u8>, Option<&'static str>), String> {
+    match format {
+        SigningFormat::Raw => Ok((message.to_vec(), None)),
+        SigningFormat::Offchain => {
+            // Rejects empty messages, over-long messages and non-UTF-8 content
+            let offchain = OffchainMessage::new(OFFCHAIN_VERSION, message)
+                .map_err(|_| "Message is not valid for the off-chain message format".to_string())?;
+            let serialized = offchain
+                .serialize()
+                .map_err(|_| "Message is not valid for the off-chain message format".to_string())?;
+
+            Ok((serialized, Some(format_name(offchain.get_format()))))
+        }
+    }
+}
+
+fn format_name(format: MessageFormat) -> &'static str {
+    match format {
+        MessageFormat::RestrictedAscii => "restrictedAscii",
+        MessageFormat::LimitedUtf8 => "limitedUtf8",
+        MessageFormat::ExtendedUtf8 => "extendedUtf8",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_matches_the_fixed_layout() {
+        let (payload, format) = signing_payload(SigningFormat::Offchain, b"hello").unwrap();
+
+        let mut expected = b"\xffsolana offchain".to_vec();
+        expected.push(0); // header version
+        expected.push(0); // restricted ASCII
+        expected.extend_from_slice(&5u16.to_le_bytes());
+        expected.extend_from_slice(b"hello");
+        assert_eq!(payload, expected);
+        assert_eq!(format, Some("restrictedAscii"));
+    }
+
+    #[test]
+    fn format_byte_follows_the_content() {
+        // 16 domain bytes, then the version at 16, the format at 17 and the length at 18..20
+        let (payload, format) = signing_payload(SigningFormat::Offchain, "héllo".as_bytes()).unwrap();
+        assert_eq!(payload[17], 1);
+        assert_eq!(&payload[18..20], &6u16.to_le_bytes());
+        assert_eq!(format, Some("limitedUtf8"));
+
+        let long = "é".repeat(1000);
+        let (payload, format) = signing_payload(SigningFormat::Offchain, long.as_bytes()).unwrap();
+        assert_eq!(payload[17], 2);
+        assert_eq!(&payload[18..20], &2000u16.to_le_bytes());
+        assert_eq!(format, Some("extendedUtf8"));
+    }
+
+    #[test]
+    fn raw_messages_pass_through() {
+        assert_eq!(signing_payload(SigningFormat::Raw, b"\xff\x00").unwrap(), (vec![0xff, 0], None));
+    }
+
+    #[test]
+    fn invalid_envelope_content_is_rejected() {
+        assert!(signing_payload(SigningFormat::Offchain, b"").is_err());
+        assert!(signing_payload(SigningFormat::Offchain, b"\xff\xfe").is_err());
+    }
+}
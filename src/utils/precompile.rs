@@ -0,0 +1,236 @@
+use sha3::{Digest, Keccak256};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::{ed25519_program, secp256k1_program};
+
+// Instruction data layouts of the native signature verification programs. Both start with
+// a signature count followed by one fixed-size offsets struct per signature; the offsets
+// point into a data area where each signature's key, signature and message are laid out
+// back to back.
+
+const ED25519_OFFSETS_START: usize = 2; // count + padding byte for alignment
+const ED25519_OFFSETS_SIZE: usize = 14;
+// Instruction index sentinel meaning "this instruction"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+const SECP256K1_OFFSETS_START: usize = 1;
+const SECP256K1_OFFSETS_SIZE: usize = 11;
+
+pub struct Ed25519Entry {
+    pub pubkey: [u8; 32],
+    pub signature: [u8; 64],
+    pub message: Vec<u8>,
+}
+
+pub struct Secp256k1Entry {
+    pub eth_address: [u8; 20],
+    pub signature: [u8; 64],
+    pub recovery_id: u8,
+    pub message: Vec<u8>,
+}
+
+/// Builds one Ed25519 program instruction verifying every entry, with all data inline.
+pub fn ed25519_instruction(entries: &[Ed25519Entry]) -> Result<Instruction, String> {
+    let count = signature_count(entries.len())?;
+    let data_start = ED25519_OFFSETS_START + entries.len() * ED25519_OFFSETS_SIZE;
+
+    let mut header = vec![count, 0];
+    let mut body = Vec::new();
+
+    for entry in entries {
+        let public_key_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.pubkey);
+        let signature_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.signature);
+        let message_data_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.message);
+        let message_data_size = offset(entry.message.len())?;
+
+        for field in [
+            signature_offset,
+            ED25519_CURRENT_INSTRUCTION,
+            public_key_offset,
+            ED25519_CURRENT_INSTRUCTION,
+            message_data_offset,
+            message_data_size,
+            ED25519_CURRENT_INSTRUCTION,
+        ] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+    }
+
+    header.extend_from_slice(&body);
+    offset(header.len())?;
+
+    Ok(Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data: header,
+    })
+}
+
+/// Builds one Secp256k1 program instruction verifying every entry. The program has no
+/// "current instruction" sentinel, so `instruction_index` must be the position this
+/// instruction will have in the transaction.
+pub fn secp256k1_instruction(entries: &[Secp256k1Entry], instruction_index: u8) -> Result<Instruction, String> {
+    let count = signature_count(entries.len())?;
+    let data_start = SECP256K1_OFFSETS_START + entries.len() * SECP256K1_OFFSETS_SIZE;
+
+    let mut header = vec![count];
+    let mut body = Vec::new();
+
+    for entry in entries {
+        let eth_address_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.eth_address);
+        let signature_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.signature);
+        body.push(entry.recovery_id);
+        let message_data_offset = offset(data_start + body.len())?;
+        body.extend_from_slice(&entry.message);
+        let message_data_size = offset(entry.message.len())?;
+
+        header.extend_from_slice(&signature_offset.to_le_bytes());
+        header.push(instruction_index);
+        header.extend_from_slice(&eth_address_offset.to_le_bytes());
+        header.push(instruction_index);
+        header.extend_from_slice(&message_data_offset.to_le_bytes());
+        header.extend_from_slice(&message_data_size.to_le_bytes());
+        header.push(instruction_index);
+    }
+
+    header.extend_from_slice(&body);
+    offset(header.len())?;
+
+    Ok(Instruction {
+        program_id: secp256k1_program::id(),
+        accounts: vec![],
+        data: header,
+    })
+}
+
+/// Ethereum address (last 20 bytes of keccak256 of the uncompressed key) of a secp256k1 key.
+pub fn eth_address(pubkey: &libsecp256k1::PublicKey) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&Keccak256::digest(&pubkey.serialize()[1..])[12..]);
+    address
+}
+
+/// The secp256k1 program checks signatures over keccak256(message).
+pub fn secp256k1_message_hash(message: &[u8]) -> libsecp256k1::Message {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Keccak256::digest(message));
+    libsecp256k1::Message::parse(&hash)
+}
+
+fn signature_count(len: usize) -> Result<u8, String> {
+    if len == 0 {
+        return Err("At least one signature is required".to_string());
+    }
+    u8::try_from(len).map_err(|_| "Too many signatures for one instruction".to_string())
+}
+
+fn offset(value: usize) -> Result<u16, String> {
+    u16::try_from(value).map_err(|_| "Instruction data too large".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::feature_set::FeatureSet;
+    use solana_sdk::signer::{keypair::Keypair, Signer};
+    use solana_sdk::{ed25519_instruction, secp256k1_instruction};
+
+    fn u16_at(data: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([data[at], data[at + 1]])
+    }
+
+    fn ed25519_entry(message: &[u8]) -> Ed25519Entry {
+        let keypair = Keypair::new();
+        Ed25519Entry {
+            pubkey: keypair.pubkey().to_bytes(),
+            signature: keypair.sign_message(message).into(),
+            message: message.to_vec(),
+        }
+    }
+
+    fn secp256k1_entry(secret: &libsecp256k1::SecretKey, message: &[u8]) -> Secp256k1Entry {
+        let (signature, recovery_id) = libsecp256k1::sign(&secp256k1_message_hash(message), secret);
+        Secp256k1Entry {
+            eth_address: eth_address(&libsecp256k1::PublicKey::from_secret_key(secret)),
+            signature: signature.serialize(),
+            recovery_id: recovery_id.serialize(),
+            message: message.to_vec(),
+        }
+    }
+
+    #[test]
+    fn ed25519_layout() {
+        let entries = [ed25519_entry(b"first"), ed25519_entry(b"second message")];
+        let data = ed25519_instruction(&entries).unwrap().data;
+
+        assert_eq!(&data[..2], &[2, 0]);
+        for (i, entry) in entries.iter().enumerate() {
+            // signature, its instruction, pubkey, its instruction, message, size, its instruction
+            let at = ED25519_OFFSETS_START + i * ED25519_OFFSETS_SIZE;
+            let field = |n: usize| u16_at(&data, at + 2 * n) as usize;
+            assert_eq!(&data[field(0)..field(0) + 64], &entry.signature);
+            assert_eq!(&data[field(2)..field(2) + 32], &entry.pubkey);
+            assert_eq!(&data[field(4)..field(4) + field(5)], entry.message.as_slice());
+            for index in [1, 3, 6] {
+                assert_eq!(field(index), u16::MAX as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn ed25519_instruction_passes_the_program_check() {
+        let entries = [ed25519_entry(b"first"), ed25519_entry(b"second message")];
+        let mut data = ed25519_instruction(&entries).unwrap().data;
+        ed25519_instruction::verify(&data, &[&data], &FeatureSet::all_enabled()).unwrap();
+
+        *data.last_mut().unwrap() ^= 1;
+        assert!(ed25519_instruction::verify(&data, &[&data], &FeatureSet::all_enabled()).is_err());
+    }
+
+    #[test]
+    fn secp256k1_layout() {
+        let secret = libsecp256k1::SecretKey::parse(&[7; 32]).unwrap();
+        let entries = [secp256k1_entry(&secret, b"first"), secp256k1_entry(&secret, b"second message")];
+        let data = secp256k1_instruction(&entries, 3).unwrap().data;
+
+        assert_eq!(data[0], 2);
+        for (i, entry) in entries.iter().enumerate() {
+            // signature (u16), its instruction (u8), address (u16), its instruction,
+            // message (u16), size (u16), its instruction
+            let at = SECP256K1_OFFSETS_START + i * SECP256K1_OFFSETS_SIZE;
+            let (signature, address) = (u16_at(&data, at) as usize, u16_at(&data, at + 3) as usize);
+            let (message, size) = (u16_at(&data, at + 6) as usize, u16_at(&data, at + 8) as usize);
+            assert_eq!([data[at + 2], data[at + 5], data[at + 10]], [3, 3, 3]);
+            assert_eq!(&data[signature..signature + 64], &entry.signature);
+            assert_eq!(data[signature + 64], entry.recovery_id);
+            assert_eq!(&data[address..address + 20], &entry.eth_address);
+            assert_eq!(&data[message..message + size], entry.message.as_slice());
+        }
+    }
+
+    #[test]
+    fn secp256k1_instruction_matches_the_sdk_and_passes_the_program_check() {
+        let secret = libsecp256k1::SecretKey::parse(&[7; 32]).unwrap();
+        let single = secp256k1_instruction(&[secp256k1_entry(&secret, b"hello")], 0).unwrap();
+        assert_eq!(single.data, secp256k1_instruction::new_secp256k1_instruction(&secret, b"hello").data);
+
+        let entries = [secp256k1_entry(&secret, b"first"), secp256k1_entry(&secret, b"second message")];
+        let data = secp256k1_instruction(&entries, 1).unwrap().data;
+        let feature_set = FeatureSet::all_enabled();
+        secp256k1_instruction::verify(&data, &[b"other", &data], &feature_set).unwrap();
+        // The offsets name instruction 1; pointed at another instruction, the check fails
+        assert!(secp256k1_instruction::verify(&data, &[&data, b"other"], &feature_set).is_err());
+    }
+
+    #[test]
+    fn signature_count_is_bounded() {
+        assert!(ed25519_instruction(&[]).is_err());
+        assert!(secp256k1_instruction(&[], 0).is_err());
+        let entries: Vec<_> = (0..256).map(|_| ed25519_entry(b"")).collect();
+        assert!(ed25519_instruction(&entries).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::ops::Deref;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// Wraps secret request values (keys, passwords, Shamir shares) so they cannot leak
+/// through `Debug`/`Display` into logs, nor through deserialization errors into API
+/// error messages. Reading the value requires an explicit `expose()`, and the value is
+/// wiped from memory when dropped.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Redacted<T: Zeroize>(T);
+
+const REDACTED: &str = "[REDACTED]";
+
+impl<T: Zeroize> Redacted<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> Zeroize for Redacted<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Redacted<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Deref for Redacted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize + Serialize> Serialize for Redacted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Redacted<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // serde messages can quote the offending input, so only the location survives
+        T::deserialize(deserializer)
+            .map(Self)
+            .map_err(|_| de::Error::custom("invalid value (redacted)"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{parse_json, ApiError};
+    use crate::models::requests::{CombineSharesRequest, KeypairRequest, SplitKeypairRequest};
+
+    // Everything a client sees of a parse error
+    fn visible(error: ApiError) -> String {
+        format!("{} {:?} {:?}", error.message, error.field, error.details)
+    }
+
+    #[test]
+    fn malformed_secrets_are_not_echoed_in_errors() {
+        let errors = [
+            parse_json::<SplitKeypairRequest>(br#"{"secret": 987654321, "shares": 3, "threshold": 2}"#).unwrap_err(),
+            parse_json::<KeypairRequest>(br#"{"password": 987654321}"#).unwrap_err(),
+            parse_json::<KeypairRequest>(br#"{"password": {"hint": "hunter987654321"}}"#).unwrap_err(),
+            parse_json::<CombineSharesRequest>(br#"{"shares": ["1-ab", 987654321], "pubkey": "x"}"#).unwrap_err(),
+        ];
+        for error in errors {
+            let visible = visible(error);
+            assert!(!visible.contains("987654321"), "{}", visible);
+            assert!(visible.contains("redacted"), "{}", visible);
+        }
+
+        // The location is kept
+        let error = parse_json::<CombineSharesRequest>(br#"{"shares": ["1-ab", 987654321], "pubkey": "x"}"#).unwrap_err();
+        assert_eq!(error.details.unwrap().path.as_deref(), Some("shares[1]"));
+
+        // Other fields still quote the input, which is what the wrapper prevents
+        let error = parse_json::<CombineSharesRequest>(br#"{"shares": [], "pubkey": 987654321}"#).unwrap_err();
+        assert!(visible(error).contains("987654321"));
+    }
+
+    #[test]
+    fn zeroize_clears_secret_buffer() {
+        let mut secret: Redacted<String> =
+            serde_json::from_str(r#""4Z7cXSyeFR8wNGMVXUE1TwtKn5D5Vu7FzEv69dokLv7KrQk7h6pu4LF8ZRR9yQBhc7uSM6RTTZtU1fmaxiNrxXrs""#)
+                .unwrap();
+        let (ptr, len) = (secret.as_ptr(), secret.len());
+
+        // The same wipe `Drop` performs, run early so the allocation can still be inspected
+        secret.zeroize();
+
+        let buffer = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(buffer.iter().all(|&b| b == 0));
+        assert!(secret.is_empty());
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+    }
+}
@@ -0,0 +1,167 @@
+use rand::{rngs::OsRng, RngCore};
+use zeroize::Zeroize;
+
+// Shamir secret sharing over GF(2^8) with the AES reduction polynomial (x^8 + x^4 + x^3 + x + 1).
+// Each byte of the secret is the constant term of its own random polynomial of degree
+// `threshold - 1`. A share is `[x, f_0(x), f_1(x), ...]` with x in 1..=255.
+
+pub fn split(secret: &[u8], shares: u8, threshold: u8) -> Result<Vec<Vec<u8>>, String> {
+    if secret.is_empty() {
+        return Err("Secret must not be empty".to_string());
+    }
+    if threshold < 2 {
+        return Err("Threshold must be at least 2".to_string());
+    }
+    if shares < threshold {
+        return Err("Share count must be at least the threshold".to_string());
+    }
+
+    let mut coefficients = vec![0u8; secret.len() * (threshold as usize - 1)];
+    OsRng.fill_bytes(&mut coefficients);
+
+    let result = (1..=shares)
+        .map(|x| {
+            let mut share = Vec::with_capacity(secret.len() + 1);
+            share.push(x);
+            for (i, &byte) in secret.iter().enumerate() {
+                let coeffs = &coefficients[i * (threshold as usize - 1)..(i + 1) * (threshold as usize - 1)];
+                // Horner evaluation, highest degree first
+                let mut y = 0u8;
+                for &c in coeffs.iter().rev() {
+                    y = gf_mul(y, x) ^ c;
+                }
+                share.push(gf_mul(y, x) ^ byte);
+            }
+            share
+        })
+        .collect();
+
+    // Coefficients are as sensitive as the secret itself
+    coefficients.zeroize();
+
+    Ok(result)
+}
+
+/// Reconstructs the secret by Lagrange interpolation at x = 0. Supplying fewer shares than
+/// the split threshold yields a wrong secret rather than an error, so callers must check
+/// the result against something known (e.g. the expected public key).
+pub fn combine(shares: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    if shares.len() < 2 {
+        return Err("At least 2 shares are required".to_string());
+    }
+
+    let len = shares[0].len();
+    if len < 2 || shares.iter().any(|s| s.len() != len) {
+        return Err("Shares have inconsistent lengths".to_string());
+    }
+
+    let xs: Vec<u8> = shares.iter().map(|s| s[0]).collect();
+    for (i, &x) in xs.iter().enumerate() {
+        if x == 0 || xs[..i].contains(&x) {
+            return Err("Shares must have distinct, non-zero indices".to_string());
+        }
+    }
+
+    // Lagrange basis values at zero: l_i(0) = prod_{j != i} x_j / (x_j - x_i)
+    let basis: Vec<u8> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            xs.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(1u8, |acc, (_, &xj)| gf_mul(acc, gf_mul(xj, gf_inv(xj ^ xi))))
+        })
+        .collect();
+
+    let secret = (1..len)
+        .map(|byte| {
+            shares
+                .iter()
+                .zip(&basis)
+                .fold(0u8, |acc, (share, &l)| acc ^ gf_mul(share[byte], l))
+        })
+        .collect();
+
+    Ok(secret)
+}
+
+// Branch-free multiplication so timing does not depend on secret bytes
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+// a^254 == a^-1 in GF(2^8)
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every subset of `shares` with exactly `size` elements
+    fn subsets(shares: &[Vec<u8>], size: usize) -> Vec<Vec<Vec<u8>>> {
+        (0u32..1 << shares.len())
+            .filter(|mask| mask.count_ones() as usize == size)
+            .map(|mask| (0..shares.len()).filter(|i| mask & (1 << i) != 0).map(|i| shares[i].clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn any_threshold_shares_reproduce_the_secret() {
+        let secret: Vec<u8> = (0..64).collect();
+        let shares = split(&secret, 5, 3).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for size in 3..=5 {
+            for subset in subsets(&shares, size) {
+                assert_eq!(combine(&subset).unwrap(), secret);
+            }
+        }
+    }
+
+    #[test]
+    fn fewer_shares_do_not() {
+        let secret: Vec<u8> = (0..64).collect();
+        let shares = split(&secret, 5, 3).unwrap();
+
+        for subset in subsets(&shares, 2) {
+            assert_ne!(combine(&subset).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn invalid_shares_are_rejected() {
+        let shares = split(b"secret", 3, 2).unwrap();
+        assert!(combine(&shares[..1]).is_err());
+        assert!(combine(&[shares[0].clone(), shares[0].clone()]).is_err());
+        assert!(combine(&[shares[0].clone(), shares[1][..3].to_vec()]).is_err());
+        assert!(split(b"secret", 3, 1).is_err());
+        assert!(split(b"secret", 2, 3).is_err());
+    }
+
+    #[test]
+    fn field_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1, "{}", a);
+        }
+    }
+}
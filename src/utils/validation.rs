@@ -11,7 +11,7 @@ pub fn validate_base58_secret(secret: &str) -> Result<Vec<u8>, String> {
         .map_err(|_| "Invalid secret key format".to_string())
 }
 
-pub fn validate_amount(amount: u64) -> Result<(), String> {
+pub fn validate_amount(_amount: u64) -> Result<(), String> {
     // Allow zero amounts as they are valid for various Solana use cases
     Ok(())
 }
@@ -0,0 +1,300 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use base64::Engine;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use zeroize::Zeroizing;
+use crate::utils::keystore::{
+    derive_key, open, seal, KdfParams, DEFAULT_LOG_N, DEFAULT_P, DEFAULT_R, KEY_LEN, SALT_LEN,
+};
+use super::{keypair_from_bytes, new_record, KeyRecord, KeyStore, VaultError};
+
+const VAULT_VERSION: u8 = 1;
+// Sealed under the master key so a wrong password is detected when the vault is opened
+const CHECK_PLAINTEXT: &[u8] = b"solana-http-server key vault";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct VaultFile {
+    version: u8,
+    kdfparams: KdfParams,
+    check_nonce: String,
+    check: String,
+    keys: Vec<SealedKey>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SealedKey {
+    #[serde(flatten)]
+    record: KeyRecord,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Key store persisted to a single JSON file. Every secret is encrypted with AES-256-GCM
+/// under a master key derived once (scrypt) from the vault password.
+pub struct FileKeyStore {
+    path: PathBuf,
+    key: Zeroizing<[u8; KEY_LEN]>,
+    state: Mutex<VaultFile>,
+    // Serializes changes, which hold it across the disk write; `state` is only ever held
+    // briefly, so lookups never wait for an fsync
+    writes: Mutex<()>,
+}
+
+impl FileKeyStore {
+    /// Opens the vault at `path`, creating an empty one if the file does not exist yet.
+    pub fn open(path: impl AsRef<Path>, password: &str) -> Result<Self, VaultError> {
+        let path = path.as_ref().to_path_buf();
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        if path.exists() {
+            let contents = fs::read(&path).map_err(|e| VaultError::Storage(e.to_string()))?;
+            let file: VaultFile = serde_json::from_slice(&contents)
+                .map_err(|_| VaultError::Storage("unreadable vault file".to_string()))?;
+            if file.version != VAULT_VERSION {
+                return Err(VaultError::Storage("unsupported vault version".to_string()));
+            }
+
+            let salt = engine
+                .decode(&file.kdfparams.salt)
+                .map_err(|_| VaultError::Storage("unreadable vault file".to_string()))?;
+            let params = &file.kdfparams;
+            let key = derive_key(password, &salt, params.log_n, params.r, params.p).map_err(VaultError::Storage)?;
+
+            let check_nonce = engine.decode(&file.check_nonce).unwrap_or_default();
+            let check = engine.decode(&file.check).unwrap_or_default();
+            if open(&key, &check_nonce, &check, &[]).ok().as_deref().map(Vec::as_slice) != Some(CHECK_PLAINTEXT) {
+                return Err(VaultError::Storage("wrong vault password".to_string()));
+            }
+
+            return Ok(Self { path, key, state: Mutex::new(file), writes: Mutex::new(()) });
+        }
+
+        if password.is_empty() {
+            return Err(VaultError::Storage("vault password must not be empty".to_string()));
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key(password, &salt, DEFAULT_LOG_N, DEFAULT_R, DEFAULT_P).map_err(VaultError::Storage)?;
+        let (check_nonce, check) = seal(&key, CHECK_PLAINTEXT, &[]).map_err(VaultError::Storage)?;
+
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            kdfparams: KdfParams {
+                log_n: DEFAULT_LOG_N,
+                r: DEFAULT_R,
+                p: DEFAULT_P,
+                salt: engine.encode(salt),
+            },
+            check_nonce: engine.encode(check_nonce),
+            check: engine.encode(check),
+            keys: Vec::new(),
+        };
+
+        let store = Self { path, key, state: Mutex::new(file.clone()), writes: Mutex::new(()) };
+        store.persist(&file)?;
+        Ok(store)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, VaultFile>, VaultError> {
+        self.state.lock().map_err(|_| VaultError::Storage("lock poisoned".to_string()))
+    }
+
+    fn lock_writes(&self) -> Result<std::sync::MutexGuard<'_, ()>, VaultError> {
+        self.writes.lock().map_err(|_| VaultError::Storage("lock poisoned".to_string()))
+    }
+
+    // Applies `change` to a copy of the vault and publishes the copy once it is on disk,
+    // so a failed write changes nothing
+    fn update<T>(&self, change: impl FnOnce(&mut VaultFile) -> Result<T, VaultError>) -> Result<T, VaultError> {
+        let _writes = self.lock_writes()?;
+        let mut next = self.lock()?.clone();
+        let result = change(&mut next)?;
+        self.persist(&next)?;
+        *self.lock()? = next;
+        Ok(result)
+    }
+
+    // Write to a sibling temp file and rename so a crash never leaves a truncated vault.
+    // Both the file and the rename are synced before a change is reported as stored.
+    // Blocking; async callers go through `vault::blocking`.
+    fn persist(&self, file: &VaultFile) -> Result<(), VaultError> {
+        let storage = |e: std::io::Error| VaultError::Storage(e.to_string());
+        let contents = serde_json::to_vec_pretty(file).map_err(|e| VaultError::Storage(e.to_string()))?;
+        let tmp = self.path.with_extension("tmp");
+
+        let mut out = File::create(&tmp).map_err(storage)?;
+        out.write_all(&contents).map_err(storage)?;
+        out.sync_all().map_err(storage)?;
+        drop(out);
+
+        fs::rename(&tmp, &self.path).map_err(storage)?;
+        sync_parent(&self.path).map_err(storage)
+    }
+}
+
+// Makes the rename durable; directories cannot be opened for syncing on Windows
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+impl KeyStore for FileKeyStore {
+    fn insert(&self, keypair: &Keypair, label: Option<String>) -> Result<KeyRecord, VaultError> {
+        let record = new_record(keypair.pubkey().to_string(), label);
+        let secret = Zeroizing::new(keypair.to_bytes());
+        let (nonce, ciphertext) = seal(&self.key, &*secret, record.pubkey.as_bytes())
+            .map_err(VaultError::Storage)?;
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let sealed = SealedKey {
+            record: record.clone(),
+            nonce: engine.encode(nonce),
+            ciphertext: engine.encode(ciphertext),
+        };
+        self.update(|file| {
+            file.keys.push(sealed);
+            Ok(())
+        })?;
+        Ok(record)
+    }
+
+    fn get(&self, key_id: &str) -> Result<KeyRecord, VaultError> {
+        let state = self.lock()?;
+        state
+            .keys
+            .iter()
+            .find(|k| k.record.key_id == key_id)
+            .map(|k| k.record.clone())
+            .ok_or(VaultError::NotFound)
+    }
+
+    fn keypair(&self, key_id: &str) -> Result<Keypair, VaultError> {
+        let sealed = {
+            let state = self.lock()?;
+            state
+                .keys
+                .iter()
+                .find(|k| k.record.key_id == key_id)
+                .cloned()
+                .ok_or(VaultError::NotFound)?
+        };
+        if sealed.record.disabled {
+            return Err(VaultError::Disabled);
+        }
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let nonce = engine.decode(&sealed.nonce).unwrap_or_default();
+        let ciphertext = engine.decode(&sealed.ciphertext).unwrap_or_default();
+        let secret = open(&self.key, &nonce, &ciphertext, sealed.record.pubkey.as_bytes())
+            .map_err(|_| VaultError::Storage("corrupt key material".to_string()))?;
+
+        keypair_from_bytes(&secret)
+    }
+
+    fn list(&self) -> Result<Vec<KeyRecord>, VaultError> {
+        let state = self.lock()?;
+        Ok(state.keys.iter().map(|k| k.record.clone()).collect())
+    }
+
+    fn set_disabled(&self, key_id: &str, disabled: bool) -> Result<KeyRecord, VaultError> {
+        self.update(|file| {
+            let key = file.keys.iter_mut().find(|k| k.record.key_id == key_id).ok_or(VaultError::NotFound)?;
+            key.record.disabled = disabled;
+            Ok(key.record.clone())
+        })
+    }
+
+    fn delete(&self, key_id: &str) -> Result<(), VaultError> {
+        self.update(|file| {
+            let index = file.keys.iter().position(|k| k.record.key_id == key_id).ok_or(VaultError::NotFound)?;
+            file.keys.remove(index);
+            Ok(())
+        })
+    }
+
+    // The vault is only ever replaced through `persist`, so it must exist and stay writable
+    fn health(&self) -> Result<(), VaultError> {
+        let _writes = self.lock_writes()?;
+        let metadata = fs::metadata(&self.path).map_err(|e| VaultError::Storage(e.to_string()))?;
+        if metadata.permissions().readonly() {
+            return Err(VaultError::Storage("vault file is read-only".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("solana-http-server-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.json");
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn keys_survive_reopening_and_legacy_records_load() {
+        let path = vault_path("reopen");
+        let keypair = Keypair::new();
+        let stored = FileKeyStore::open(&path, "pw").unwrap().insert(&keypair, Some("hot".to_string())).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("\"createdAt\"") && !contents.contains("\"created_at\""));
+
+        // Files written before the casing fix used `created_at`
+        fs::write(&path, contents.replace("\"createdAt\"", "\"created_at\"")).unwrap();
+        let vault = FileKeyStore::open(&path, "pw").unwrap();
+        let record = vault.get(&stored.key_id).unwrap();
+        assert_eq!(record.created_at, stored.created_at);
+        assert_eq!(record.label.as_deref(), Some("hot"));
+        assert_eq!(vault.keypair(&stored.key_id).unwrap().pubkey(), keypair.pubkey());
+
+        assert!(FileKeyStore::open(&path, "wrong").is_err());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn lookups_do_not_wait_for_writes() {
+        let path = vault_path("writes");
+        let vault = FileKeyStore::open(&path, "pw").unwrap();
+        let record = vault.insert(&Keypair::new(), None).unwrap();
+
+        // A change in progress holds the write lock across the disk write
+        let _writing = vault.lock_writes().unwrap();
+        assert!(vault.keypair(&record.key_id).is_ok());
+        assert_eq!(vault.list().unwrap().len(), 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn failed_writes_change_nothing() {
+        let path = vault_path("failed");
+        let vault = FileKeyStore::open(&path, "pw").unwrap();
+        let record = vault.insert(&Keypair::new(), None).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(vault.insert(&Keypair::new(), None).is_err());
+        assert!(vault.set_disabled(&record.key_id, true).is_err());
+        assert!(vault.delete(&record.key_id).is_err());
+        assert_eq!(vault.list().unwrap().len(), 1);
+        assert!(!vault.get(&record.key_id).unwrap().disabled);
+    }
+}
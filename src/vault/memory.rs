@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use zeroize::Zeroizing;
+use super::{keypair_from_bytes, new_record, KeyRecord, KeyStore, VaultError};
+
+struct Entry {
+    record: KeyRecord,
+    // Wiped when the entry is deleted or the store dropped
+    secret: Zeroizing<[u8; 64]>,
+}
+
+/// Process-local key store. Keys are lost on restart.
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn insert(&self, keypair: &Keypair, label: Option<String>) -> Result<KeyRecord, VaultError> {
+        let record = new_record(keypair.pubkey().to_string(), label);
+        let mut entries = self.entries.write().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        entries.insert(record.key_id.clone(), Entry {
+            record: record.clone(),
+            secret: Zeroizing::new(keypair.to_bytes()),
+        });
+        Ok(record)
+    }
+
+    fn get(&self, key_id: &str) -> Result<KeyRecord, VaultError> {
+        let entries = self.entries.read().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        entries.get(key_id).map(|e| e.record.clone()).ok_or(VaultError::NotFound)
+    }
+
+    fn keypair(&self, key_id: &str) -> Result<Keypair, VaultError> {
+        let entries = self.entries.read().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        let entry = entries.get(key_id).ok_or(VaultError::NotFound)?;
+        if entry.record.disabled {
+            return Err(VaultError::Disabled);
+        }
+        keypair_from_bytes(&*entry.secret)
+    }
+
+    fn list(&self) -> Result<Vec<KeyRecord>, VaultError> {
+        let entries = self.entries.read().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        let mut records: Vec<KeyRecord> = entries.values().map(|e| e.record.clone()).collect();
+        records.sort_by_key(|r| r.created_at);
+        Ok(records)
+    }
+
+    fn set_disabled(&self, key_id: &str, disabled: bool) -> Result<KeyRecord, VaultError> {
+        let mut entries = self.entries.write().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        let entry = entries.get_mut(key_id).ok_or(VaultError::NotFound)?;
+        entry.record.disabled = disabled;
+        Ok(entry.record.clone())
+    }
+
+    fn delete(&self, key_id: &str) -> Result<(), VaultError> {
+        let mut entries = self.entries.write().map_err(|_| VaultError::Storage("lock poisoned".to_string()))?;
+        entries.remove(key_id).map(|_| ()).ok_or(VaultError::NotFound)
+    }
+}
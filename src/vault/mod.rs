@@ -0,0 +1,161 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use solana_sdk::signer::keypair::Keypair;
+
+pub mod file;
+pub mod memory;
+
+pub use file::FileKeyStore;
+pub use memory::MemoryKeyStore;
+
+/// Metadata for a key held by the vault. The secret itself never leaves the store
+/// except through `KeyStore::keypair`.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRecord {
+    pub key_id: String,
+    pub pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub disabled: bool,
+    // Vault files written before the rename still carry `created_at`
+    #[serde(alias = "created_at")]
+    pub created_at: u64,
+}
+
+#[derive(Debug)]
+pub enum VaultError {
+    NotFound,
+    Disabled,
+    Storage(String),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::NotFound => write!(f, "Key not found"),
+            VaultError::Disabled => write!(f, "Key is disabled"),
+            VaultError::Storage(e) => write!(f, "Key vault error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+/// Server-side key storage addressed by opaque key ids.
+pub trait KeyStore: Send + Sync {
+    fn insert(&self, keypair: &Keypair, label: Option<String>) -> Result<KeyRecord, VaultError>;
+
+    fn get(&self, key_id: &str) -> Result<KeyRecord, VaultError>;
+
+    /// Returns the keypair for signing. Disabled keys are refused.
+    fn keypair(&self, key_id: &str) -> Result<Keypair, VaultError>;
+
+    fn list(&self) -> Result<Vec<KeyRecord>, VaultError>;
+
+    fn set_disabled(&self, key_id: &str, disabled: bool) -> Result<KeyRecord, VaultError>;
+
+    fn delete(&self, key_id: &str) -> Result<(), VaultError>;
+
+    /// Checks that the backing storage is still usable. Used by the readiness probe.
+    fn health(&self) -> Result<(), VaultError> {
+        Ok(())
+    }
+}
+
+/// Runs `op` on a blocking thread. Stores may do file I/O while holding their lock, which
+/// must not stall the async workers.
+pub async fn blocking<T, F>(vault: &Arc<dyn KeyStore>, op: F) -> Result<T, VaultError>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn KeyStore) -> Result<T, VaultError> + Send + 'static,
+{
+    let vault = vault.clone();
+    tokio::task::spawn_blocking(move || op(vault.as_ref()))
+        .await
+        .map_err(|_| VaultError::Storage("vault task failed".to_string()))?
+}
+
+pub(crate) fn new_record(pubkey: String, label: Option<String>) -> KeyRecord {
+    let mut id = [0u8; 16];
+    OsRng.fill_bytes(&mut id);
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    KeyRecord {
+        key_id: bs58::encode(id).into_string(),
+        pubkey,
+        label,
+        disabled: false,
+        created_at,
+    }
+}
+
+pub(crate) fn keypair_from_bytes(bytes: &[u8]) -> Result<Keypair, VaultError> {
+    Keypair::from_bytes(bytes).map_err(|_| VaultError::Storage("corrupt key material".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use solana_sdk::signer::Signer;
+
+    // The behaviour every backend must share
+    fn check_contract(vault: &dyn KeyStore) {
+        let first = Keypair::new();
+        let second = Keypair::new();
+        let kept = vault.insert(&first, Some("hot".to_string())).unwrap();
+        let removed = vault.insert(&second, None).unwrap();
+        assert_ne!(kept.key_id, removed.key_id);
+        assert_eq!(kept.pubkey, first.pubkey().to_string());
+        assert!(!kept.disabled);
+
+        let ids: BTreeSet<String> = vault.list().unwrap().into_iter().map(|record| record.key_id).collect();
+        assert_eq!(ids, BTreeSet::from([kept.key_id.clone(), removed.key_id.clone()]));
+        assert_eq!(vault.get(&kept.key_id).unwrap().label.as_deref(), Some("hot"));
+        assert_eq!(vault.keypair(&kept.key_id).unwrap().pubkey(), first.pubkey());
+
+        // Disabled keys stay listed but cannot sign until re-enabled
+        assert!(vault.set_disabled(&kept.key_id, true).unwrap().disabled);
+        assert!(vault.get(&kept.key_id).unwrap().disabled);
+        assert!(matches!(vault.keypair(&kept.key_id), Err(VaultError::Disabled)));
+        assert!(!vault.set_disabled(&kept.key_id, false).unwrap().disabled);
+        assert_eq!(vault.keypair(&kept.key_id).unwrap().pubkey(), first.pubkey());
+
+        vault.delete(&removed.key_id).unwrap();
+        assert_eq!(vault.list().unwrap().len(), 1);
+        assert!(matches!(vault.get(&removed.key_id), Err(VaultError::NotFound)));
+        assert!(matches!(vault.keypair(&removed.key_id), Err(VaultError::NotFound)));
+        assert!(matches!(vault.set_disabled(&removed.key_id, true), Err(VaultError::NotFound)));
+        assert!(matches!(vault.delete(&removed.key_id), Err(VaultError::NotFound)));
+
+        vault.health().unwrap();
+    }
+
+    #[test]
+    fn memory_store_meets_the_contract() {
+        check_contract(&MemoryKeyStore::new());
+    }
+
+    #[test]
+    fn file_store_meets_the_contract() {
+        let dir = std::env::temp_dir().join(format!("solana-http-server-contract-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.json");
+        let _ = std::fs::remove_file(&path);
+
+        check_contract(&FileKeyStore::open(&path, "pw").unwrap());
+        // The same state after reopening
+        let reopened = FileKeyStore::open(&path, "pw").unwrap();
+        assert_eq!(reopened.list().unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
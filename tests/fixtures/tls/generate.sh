@@ -0,0 +1,35 @@
+#!/bin/sh
+# Regenerates the TLS test fixtures: a CA, two server certificates for `localhost` and a
+# client certificate, all signed by the CA and valid for 100 years.
+set -e
+cd "$(dirname "$0")"
+
+key() {
+    openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out "$1.key"
+}
+
+sign() {
+    name=$1 subject=$2 extensions=$3
+    key "$name"
+    openssl req -new -key "$name.key" -subj "/CN=$subject" -out "$name.csr"
+    printf '%s\n' "$extensions" > "$name.ext"
+    openssl x509 -req -in "$name.csr" -CA ca.pem -CAkey ca.key -CAcreateserial -days 36500 \
+        -extfile "$name.ext" -out "$name.pem"
+    rm "$name.csr" "$name.ext"
+}
+
+key ca
+openssl req -x509 -new -key ca.key -subj "/CN=solana-http-server test CA" -days 36500 \
+    -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign" -out ca.pem
+
+server="basicConstraints=CA:FALSE
+keyUsage=digitalSignature
+extendedKeyUsage=serverAuth
+subjectAltName=DNS:localhost"
+sign server localhost "$server"
+sign server-2 localhost "$server"
+sign client automation "basicConstraints=CA:FALSE
+keyUsage=digitalSignature
+extendedKeyUsage=clientAuth"
+
+rm -f ca.key ca.srl
@@ -0,0 +1,70 @@
+//! Runs `RedisRateLimitStore` against the `stand-in-redis` binary.
+
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use solana_http_server::ratelimit::{Limit, RateLimitStore, RedisRateLimitStore};
+
+struct StandIn {
+    process: Child,
+    address: String,
+}
+
+impl StandIn {
+    async fn start(password: &str) -> Self {
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let stand_in = Self {
+            process: Command::new(env!("CARGO_BIN_EXE_stand-in-redis"))
+                .args(["--listen", &address, "--password", password])
+                .stdout(Stdio::null())
+                .spawn()
+                .expect("Failed to start stand-in-redis"),
+            address,
+        };
+
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(&stand_in.address).await.is_ok() {
+                return stand_in;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("stand-in-redis did not start");
+    }
+
+    fn store(&self, password: &str) -> RedisRateLimitStore {
+        RedisRateLimitStore::from_url(&format!("redis://:{}@{}", password, self.address)).unwrap()
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+#[tokio::test]
+async fn buckets_are_shared_through_the_store() {
+    let stand_in = StandIn::start("secret").await;
+    let (first, second) = (stand_in.store("secret"), stand_in.store("secret"));
+    let limit = Limit::parse("3/m").unwrap();
+
+    let first_two = [first.acquire("client", limit).await.unwrap(), first.acquire("client", limit).await.unwrap()];
+    assert!(first_two.iter().all(|decision| decision.allowed));
+    assert_eq!(first_two[1].remaining, 1);
+
+    // Another instance sees the same bucket
+    assert!(second.acquire("client", limit).await.unwrap().allowed);
+    let denied = second.acquire("client", limit).await.unwrap();
+    assert!(!denied.allowed);
+    assert!(denied.retry_after > Duration::ZERO);
+
+    assert!(first.acquire("other", limit).await.unwrap().allowed);
+}
+
+#[tokio::test]
+async fn wrong_password_is_an_error() {
+    let stand_in = StandIn::start("secret").await;
+    let error = stand_in.store("wrong").acquire("client", Limit::parse("3/m").unwrap()).await.unwrap_err();
+    assert!(error.0.starts_with("WRONGPASS"), "{}", error);
+}
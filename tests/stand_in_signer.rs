@@ -0,0 +1,172 @@
+//! Runs `RemoteSigner` against the `stand-in-signer` binary over both transports, and
+//! against misbehaving signers.
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use base64::Engine;
+use solana_http_server::signer::{RemoteSigner, SignerBackend, SignerError, SigningKey};
+use solana_sdk::signer::{keypair::Keypair, Signer};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+
+struct StandIn {
+    process: Child,
+    dir: PathBuf,
+    spec: String,
+}
+
+impl StandIn {
+    // Serves `keypair` under the id `hot`, over a unix socket or HTTP
+    async fn start(keypair: &Keypair, unix: bool) -> Self {
+        let dir = scratch_dir(if unix { "unix" } else { "http" });
+        let keys = dir.join("keys.txt");
+        std::fs::write(&keys, format!("hot={}\n", keypair.to_base58_string())).unwrap();
+
+        let (flag, address, spec) = if unix {
+            let socket = dir.join("signer.sock").display().to_string();
+            ("--unix", socket.clone(), format!("unix:{}", socket))
+        } else {
+            let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+            ("--http", address.clone(), format!("http://{}", address))
+        };
+        let stand_in = Self {
+            process: Command::new(env!("CARGO_BIN_EXE_stand-in-signer"))
+                .args([flag, &address, "--keys", &keys.display().to_string()])
+                .stdout(Stdio::null())
+                .spawn()
+                .expect("Failed to start stand-in-signer"),
+            dir,
+            spec,
+        };
+
+        let signer = stand_in.signer();
+        for _ in 0..100 {
+            if signer.health().await.is_ok() {
+                return stand_in;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("stand-in-signer did not start");
+    }
+
+    fn signer(&self) -> RemoteSigner {
+        RemoteSigner::from_spec(&self.spec).unwrap()
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stand-in-signer-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn handle(key_id: &str) -> SigningKey {
+    SigningKey::Handle(key_id.to_string())
+}
+
+#[tokio::test]
+async fn signs_over_both_transports() {
+    let keypair = Keypair::new();
+    for unix in [true, false] {
+        let stand_in = StandIn::start(&keypair, unix).await;
+        let signed = stand_in.signer().sign(&handle("hot"), b"hello").await.unwrap();
+        assert_eq!(signed.pubkey, keypair.pubkey(), "unix: {}", unix);
+        assert!(signed.signature.verify(keypair.pubkey().as_ref(), b"hello"));
+
+        // Refusals from the signer are rejections, not outages
+        match stand_in.signer().sign(&handle("cold"), b"hello").await {
+            Err(SignerError::Rejected(reason)) => assert_eq!(reason, "Key not found"),
+            other => panic!("unix: {}: {:?}", unix, other.map(|signed| signed.pubkey)),
+        }
+    }
+}
+
+// Answers a signing request for `message`, or never answers with `None`
+type Respond = Box<dyn Fn(&[u8]) -> Option<String> + Send>;
+
+// A unix signer answering every request with `respond(message)`
+fn fake_signer(name: &str, respond: impl Fn(&[u8]) -> Option<String> + Send + 'static) -> (RemoteSigner, PathBuf) {
+    let dir = scratch_dir(name);
+    let socket = dir.join("signer.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let message = base64::engine::general_purpose::STANDARD
+                .decode(request["message"].as_str().unwrap())
+                .unwrap();
+            match respond(&message) {
+                Some(response) => write.write_all(format!("{}\n", response).as_bytes()).await.unwrap(),
+                // Hold the connection open without answering
+                None => {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    drop(write);
+                }
+            }
+        }
+    });
+    (RemoteSigner::unix(&socket), dir)
+}
+
+#[tokio::test]
+async fn tampered_responses_are_refused() {
+    let keypair = Keypair::new();
+    let other = Keypair::new();
+
+    let responses: [(&str, Respond); 3] = [
+        // Signature over a different message
+        ("tampered-signature", Box::new({
+            let keypair = keypair.insecure_clone();
+            move |_| {
+                let signature = base64::engine::general_purpose::STANDARD.encode(keypair.sign_message(b"something else"));
+                Some(serde_json::json!({ "pubkey": keypair.pubkey().to_string(), "signature": signature }).to_string())
+            }
+        })),
+        // A valid signature, claimed for someone else's key
+        ("tampered-pubkey", Box::new({
+            let keypair = keypair.insecure_clone();
+            let other = other.pubkey();
+            move |message| {
+                let signature = base64::engine::general_purpose::STANDARD.encode(keypair.sign_message(message));
+                Some(serde_json::json!({ "pubkey": other.to_string(), "signature": signature }).to_string())
+            }
+        })),
+        ("garbage", Box::new(|_| Some("not json".to_string()))),
+    ];
+
+    for (name, respond) in responses {
+        let (signer, dir) = fake_signer(name, respond);
+        match signer.sign(&handle("hot"), b"hello").await {
+            Err(SignerError::Unavailable(_)) => {}
+            other => panic!("{}: {:?}", name, other.map(|signed| signed.pubkey)),
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn unresponsive_signers_time_out() {
+    let (signer, dir) = fake_signer("silent", |_| None);
+    let signer = signer.with_timeout(Duration::from_millis(200));
+
+    let started = std::time::Instant::now();
+    match signer.sign(&handle("hot"), b"hello").await {
+        Err(SignerError::Unavailable(reason)) => assert_eq!(reason, "timed out"),
+        other => panic!("{:?}", other.map(|signed| signed.pubkey)),
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let _ = std::fs::remove_dir_all(dir);
+}